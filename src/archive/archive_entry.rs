@@ -0,0 +1,464 @@
+use crate::cmd::command_runner::{execute_cmd, execute_cmd_get_lines};
+use crate::error::OperationError;
+use crate::file::string_utils::{find_file_extension, shell_quote};
+use crate::output::Json;
+use std::error::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveEntryKind {
+    File,
+    Directory,
+    Symlink,
+    Hardlink,
+}
+
+impl ArchiveEntryKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ArchiveEntryKind::File => "file",
+            ArchiveEntryKind::Directory => "directory",
+            ArchiveEntryKind::Symlink => "symlink",
+            ArchiveEntryKind::Hardlink => "hardlink",
+        }
+    }
+}
+
+/// Single member of an archive, as reported by `zipinfo` or `tar -tv` - nothing is extracted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    pub path: String,
+    pub kind: ArchiveEntryKind,
+    pub mode: String,
+    pub owner: Option<String>,
+    pub size: u64,
+    /// Only ZIP stores per-entry compressed sizes; tarballs are compressed as a whole.
+    pub compressed_size: Option<u64>,
+    pub mtime: String,
+    pub encrypted: bool,
+    pub link_target: Option<String>,
+}
+
+impl ArchiveEntry {
+    /// Path without the leading `./` most tar archives store.
+    #[inline]
+    pub fn normalized_path(&self) -> &str {
+        self.path.trim_start_matches("./")
+    }
+
+    /// Share of the size saved by compression, e.g. `0.75` when an entry shrunk to a quarter.
+    pub fn ratio(&self) -> Option<f64> {
+        match self.compressed_size {
+            Some(compressed) if self.size > 0 => Some(1.0 - compressed as f64 / self.size as f64),
+            _ => None,
+        }
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("path", Json::from(self.path.as_str())),
+            ("kind", Json::from(self.kind.name())),
+            ("mode", Json::from(self.mode.as_str())),
+            ("owner", Json::from(self.owner.clone())),
+            ("size", Json::from(self.size)),
+            ("compressed_size", Json::from(self.compressed_size)),
+            ("ratio", Json::from(self.ratio())),
+            ("mtime", Json::from(self.mtime.as_str())),
+            ("encrypted", Json::from(self.encrypted)),
+            ("link_target", Json::from(self.link_target.clone())),
+        ])
+    }
+}
+
+/// Entries of the archive; empty for an empty, but valid, archive.
+pub fn read_entries(archive: &str) -> Result<Vec<ArchiveEntry>, Box<dyn Error>> {
+    let tool = list_tool(archive)?;
+    let quoted = shell_quote(archive);
+    let entries: Vec<ArchiveEntry> = match tool {
+        "zipinfo" => {
+            let lines = execute_cmd_get_lines(&format!("zipinfo -l -T {quoted}"));
+            let entries = lines
+                .iter()
+                .filter_map(|line| parse_zipinfo_line(line))
+                .collect::<Vec<ArchiveEntry>>();
+            // `zipinfo` fails on an empty archive, its header tells it apart from a broken one
+            let is_empty = lines
+                .iter()
+                .any(|line| line.ends_with("number of entries: 0"));
+            if is_empty {
+                return Ok(entries);
+            }
+            entries
+        }
+        _ => {
+            let entries = execute_cmd_get_lines(&format!("{tool} -tvf {quoted}"))
+                .iter()
+                .filter_map(|line| parse_tar_line(line))
+                .collect::<Vec<ArchiveEntry>>();
+            if entries.is_empty() && execute_cmd(&format!("{tool} -tf {quoted}")).is_ok() {
+                return Ok(entries);
+            }
+            entries
+        }
+    };
+
+    match entries.is_empty() {
+        true => Err(OperationError::CouldNotReadArchive(archive.to_owned()).into()),
+        false => Ok(entries),
+    }
+}
+
+/// `zipinfo` for ZIP based formats, GNU `tar` for tarballs and `bsdtar` (libarchive) for the other
+/// supported formats, e.g. `.rar` and `.cab`.
+fn list_tool(archive: &str) -> Result<&'static str, Box<dyn Error>> {
+    let extension = find_file_extension(archive).unwrap_or_default();
+    match extension.as_str() {
+        ".zip" | ".cbz" => Ok("zipinfo"),
+        ".rar" | ".ar" | ".cab" | ".7z" => match execute_cmd("command -v bsdtar") {
+            Ok(_) => Ok("bsdtar"),
+            Err(_) => Err(OperationError::UnsupportedFormat(
+                extension,
+                "bsdtar (libarchive) is not installed".to_owned(),
+            )
+            .into()),
+        },
+        ".zoo" => Err(OperationError::UnsupportedFormat(
+            extension,
+            "no supported tool reads them".to_owned(),
+        )
+        .into()),
+        _ => Ok("tar"),
+    }
+}
+
+/// Finds the entry by its path, ignoring leading `./` and trailing `/`.
+pub fn find_entry<'a>(
+    entries: &'a [ArchiveEntry],
+    path: &str,
+) -> Result<&'a ArchiveEntry, Box<dyn Error>> {
+    let normalized = path.trim_start_matches("./").trim_end_matches('/');
+    entries
+        .iter()
+        .find(|entry| entry.normalized_path().trim_end_matches('/') == normalized)
+        .ok_or_else(|| OperationError::EntryNotFound(path.to_owned()).into())
+}
+
+/// Parses `zipinfo -l -T` line, e.g. `-rw-r--r--  3.0 unx  8 tx  8 stor 20250714.175100 dir/file.txt`.
+fn parse_zipinfo_line(line: &str) -> Option<ArchiveEntry> {
+    let (fields, path) = split_fields(line, 8)?;
+    let mode = fields[0];
+    let size = fields[3].parse::<u64>().ok()?;
+    let compressed_size = fields[5].parse::<u64>().ok()?;
+    let encrypted = fields[4].starts_with(|c: char| c.is_ascii_uppercase());
+    let mtime = format_zipinfo_time(fields[7])?;
+
+    Some(ArchiveEntry {
+        path: path.to_owned(),
+        kind: kind_from_mode(mode, path),
+        mode: mode.to_owned(),
+        owner: None,
+        size,
+        compressed_size: Some(compressed_size),
+        mtime,
+        encrypted,
+        link_target: None,
+    })
+}
+
+/// `20250714.175100` -> `2025-07-14 17:51:00`
+fn format_zipinfo_time(value: &str) -> Option<String> {
+    let (date, time) = value.split_once('.')?;
+    if date.len() != 8 || time.len() != 6 {
+        return None;
+    }
+    Some(format!(
+        "{}-{}-{} {}:{}:{}",
+        &date[..4],
+        &date[4..6],
+        &date[6..],
+        &time[..2],
+        &time[2..4],
+        &time[4..]
+    ))
+}
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Modification time as `[year, month, day, hour, minute, second]`, so times of different listings
+/// compare: `2025-07-14 17:51[:00]` of `zipinfo` and GNU tar, `Jul 14 17:51` and `Jul 14 2024` of bsdtar.
+/// bsdtar leaves the year out for the last six months, so it is taken from `today` (year, month, day).
+pub fn parse_mtime(mtime: &str, today: (u32, u32, u32)) -> Option<[u32; 6]> {
+    let numbers = |value: &str, separator: char| {
+        value
+            .split(separator)
+            .map(|part| part.parse::<u32>().ok())
+            .collect::<Option<Vec<u32>>>()
+    };
+    let time = |value: &str| match numbers(value, ':')?.as_slice() {
+        [hour, minute] => Some([*hour, *minute, 0]),
+        [hour, minute, second] => Some([*hour, *minute, *second]),
+        _ => None,
+    };
+    match mtime.split_whitespace().collect::<Vec<&str>>().as_slice() {
+        [date, clock] => {
+            let [year, month, day]: [u32; 3] = numbers(date, '-')?.try_into().ok()?;
+            let [hour, minute, second] = time(clock)?;
+            Some([year, month, day, hour, minute, second])
+        }
+        [month, day, year_or_clock] => {
+            let month = MONTHS.iter().position(|name| name == month)? as u32 + 1;
+            let day = day.parse::<u32>().ok()?;
+            if !year_or_clock.contains(':') {
+                return Some([year_or_clock.parse().ok()?, month, day, 0, 0, 0]);
+            }
+            let [hour, minute, second] = time(year_or_clock)?;
+            let (year, today_month, today_day) = today;
+            // a later day of the year than today is from the last year
+            let year = match (month, day) > (today_month, today_day) {
+                true => year - 1,
+                false => year,
+            };
+            Some([year, month, day, hour, minute, second])
+        }
+        _ => None,
+    }
+}
+
+/// Parses `tar -tv` line of GNU tar (`-rw-r--r-- user/group 8 2025-07-14 17:51 ./file`)
+/// or bsdtar (`-rw-r--r--  0 user group 8 Jul 14 17:51 ./file`).
+fn parse_tar_line(line: &str) -> Option<ArchiveEntry> {
+    let first_fields = line.split_whitespace().take(2).collect::<Vec<&str>>();
+    if first_fields.len() < 2 || first_fields[0].len() != 10 {
+        return None;
+    }
+    let is_gnu = first_fields[1].contains('/');
+    let (fields, name) = match is_gnu {
+        true => split_fields(line, 5)?,
+        false => split_fields(line, 8)?,
+    };
+    let (owner, size, mtime) = match is_gnu {
+        true => (
+            fields[1].to_owned(),
+            fields[2],
+            format!("{} {}", fields[3], fields[4]),
+        ),
+        false => (
+            format!("{}/{}", fields[2], fields[3]),
+            fields[4],
+            format!("{} {} {}", fields[5], fields[6], fields[7]),
+        ),
+    };
+    let size = size.parse::<u64>().ok()?;
+    let mode = fields[0];
+
+    let (path, link_target, kind) = if let Some((path, target)) = name.split_once(" -> ") {
+        (path, Some(target), ArchiveEntryKind::Symlink)
+    } else if let Some((path, target)) = name.split_once(" link to ") {
+        (path, Some(target), ArchiveEntryKind::Hardlink)
+    } else {
+        (name, None, kind_from_mode(mode, name))
+    };
+
+    Some(ArchiveEntry {
+        path: path.to_owned(),
+        kind,
+        mode: mode.to_owned(),
+        owner: Some(owner),
+        size,
+        compressed_size: None,
+        mtime,
+        encrypted: false,
+        link_target: link_target.map(|target| target.to_owned()),
+    })
+}
+
+#[inline]
+fn kind_from_mode(mode: &str, path: &str) -> ArchiveEntryKind {
+    match mode.chars().next() {
+        Some('d') => ArchiveEntryKind::Directory,
+        Some('l') => ArchiveEntryKind::Symlink,
+        Some('h') => ArchiveEntryKind::Hardlink,
+        _ if path.ends_with('/') => ArchiveEntryKind::Directory,
+        _ => ArchiveEntryKind::File,
+    }
+}
+
+/// Splits off `count` whitespace separated fields and returns them with the untouched rest of the line.
+fn split_fields(line: &str, count: usize) -> Option<(Vec<&str>, &str)> {
+    let mut fields = Vec::with_capacity(count);
+    let mut rest = line.trim_start();
+    for _ in 0..count {
+        let end = rest.find(char::is_whitespace)?;
+        fields.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    match rest.is_empty() {
+        true => None,
+        false => Some((fields, rest)),
+    }
+}
+
+/// Regular-looking entry for tests, other fields set with struct update syntax.
+#[cfg(test)]
+pub fn test_entry(path: &str, kind: ArchiveEntryKind) -> ArchiveEntry {
+    ArchiveEntry {
+        path: path.to_owned(),
+        kind,
+        mode: "-rw-r--r--".to_owned(),
+        owner: None,
+        size: 0,
+        compressed_size: None,
+        mtime: "2025-07-14 17:51".to_owned(),
+        encrypted: false,
+        link_target: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::temp_dir::TempDir;
+    use std::fs;
+
+    #[test]
+    fn should_parse_zipinfo_line() {
+        let entry = parse_zipinfo_line(
+            "-rw-r--r--  3.0 unx      800 TX      200 defN 20250714.175100 my dir/file.txt",
+        )
+        .unwrap();
+        assert_eq!(entry.path, "my dir/file.txt");
+        assert_eq!(entry.kind, ArchiveEntryKind::File);
+        assert_eq!(entry.size, 800);
+        assert_eq!(entry.compressed_size, Some(200));
+        assert_eq!(entry.ratio(), Some(0.75));
+        assert_eq!(entry.mtime, "2025-07-14 17:51:00");
+        assert!(entry.encrypted);
+
+        assert!(parse_zipinfo_line("Zip file size: 1164 bytes, number of entries: 7").is_none());
+        assert!(
+            parse_zipinfo_line("7 files, 16 bytes uncompressed, 16 bytes compressed:  0.0%")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn should_parse_gnu_tar_line() {
+        let entry =
+            parse_tar_line("-rw-r--r-- robert/staff     163 2025-07-14 17:51 ./resources/a b.txt")
+                .unwrap();
+        assert_eq!(entry.path, "./resources/a b.txt");
+        assert_eq!(entry.normalized_path(), "resources/a b.txt");
+        assert_eq!(entry.owner.as_deref(), Some("robert/staff"));
+        assert_eq!(entry.size, 163);
+        assert_eq!(entry.mtime, "2025-07-14 17:51");
+        assert_eq!(entry.ratio(), None);
+
+        let link =
+            parse_tar_line("lrwxrwxrwx root/root 0 2026-10-19 06:18 ./link -> extra").unwrap();
+        assert_eq!(link.kind, ArchiveEntryKind::Symlink);
+        assert_eq!(link.path, "./link");
+        assert_eq!(link.link_target.as_deref(), Some("extra"));
+
+        let hard = parse_tar_line("hrw-r--r-- root/root 0 2026-10-19 06:18 ./hard link to ./extra")
+            .unwrap();
+        assert_eq!(hard.kind, ArchiveEntryKind::Hardlink);
+        assert_eq!(hard.path, "./hard");
+    }
+
+    #[test]
+    fn should_parse_bsdtar_line() {
+        let entry =
+            parse_tar_line("drwxr-xr-x  0 root   root        0 Oct 19 06:18 ./resources/").unwrap();
+        assert_eq!(entry.kind, ArchiveEntryKind::Directory);
+        assert_eq!(entry.owner.as_deref(), Some("root/root"));
+        assert_eq!(entry.mtime, "Oct 19 06:18");
+    }
+
+    #[test]
+    fn should_parse_mtime() {
+        let today = (2026, 10, 19);
+        assert_eq!(
+            parse_mtime("2025-07-14 17:51", today),
+            Some([2025, 7, 14, 17, 51, 0])
+        );
+        assert_eq!(
+            parse_mtime("2025-07-14 17:51:30", today),
+            Some([2025, 7, 14, 17, 51, 30])
+        );
+        assert_eq!(
+            parse_mtime("Oct 19 06:18", today),
+            Some([2026, 10, 19, 6, 18, 0])
+        );
+        assert_eq!(
+            parse_mtime("Dec 24 10:00", today),
+            Some([2025, 12, 24, 10, 0, 0])
+        );
+        assert_eq!(
+            parse_mtime("Jan 1 1970", today),
+            Some([1970, 1, 1, 0, 0, 0])
+        );
+        assert_eq!(parse_mtime("yesterday", today), None);
+    }
+
+    #[test]
+    fn should_read_empty_and_other_formats() {
+        let dir = TempDir::new("archive_entry_test").unwrap();
+        let path = |name: &str| dir.path().join(name).display().to_string();
+        // end of central directory record only
+        let mut empty_zip = b"PK\x05\x06".to_vec();
+        empty_zip.resize(22, 0);
+        fs::write(path("empty.zip"), empty_zip).unwrap();
+        execute_cmd(&format!(
+            "tar -cf {} -T /dev/null",
+            shell_quote(&path("empty.tar"))
+        ))
+        .unwrap();
+        fs::write(path("broken.tar"), "not an archive").unwrap();
+        for archive in ["empty.zip", "empty.tar"] {
+            assert!(read_entries(&path(archive)).unwrap().is_empty());
+        }
+        assert!(read_entries(&path("broken.tar")).is_err());
+        assert!(read_entries(&path("old.zoo")).is_err());
+
+        if execute_cmd("command -v bsdtar && command -v ar").is_ok() {
+            execute_cmd(&format!(
+                "cd {} && ar rc test.ar {}",
+                shell_quote(&dir.to_string_path()),
+                shell_quote(
+                    &fs::canonicalize("./resources/test/files/test1.txt")
+                        .unwrap()
+                        .display()
+                        .to_string()
+                )
+            ))
+            .unwrap();
+            let entries = read_entries(&path("test.ar")).unwrap();
+            assert_eq!(entries[0].path, "test1.txt");
+        }
+    }
+
+    #[test]
+    fn should_find_entry() {
+        let entries = read_entries("./resources/test/archives/resources_archive.tar").unwrap();
+        let entry = find_entry(&entries, "resources/test/test2.txt").unwrap();
+        assert_eq!(entry.path, "./resources/test/test2.txt");
+
+        let dir = find_entry(&entries, "./resources/test").unwrap();
+        assert_eq!(dir.kind, ArchiveEntryKind::Directory);
+
+        assert!(find_entry(&entries, "resources/test/missing.txt").is_err());
+    }
+
+    #[test]
+    fn should_read_entries_of_each_format() {
+        for archive in ["resources_archive.tar", "resources_archive.zip"] {
+            let entries = read_entries(&format!("./resources/test/archives/{archive}")).unwrap();
+            assert!(
+                entries
+                    .iter()
+                    .any(|entry| entry.normalized_path() == "resources/test/test1.txt")
+            );
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::archive::archive_entry::{ArchiveEntry, ArchiveEntryKind};
+use crate::archive::{archive_entry, limits, path_safety, unpacker};
+use crate::cmd::command_runner::{execute_cmd_get_lines, stream_command};
+use crate::error::OperationError;
+use crate::file::file_metadata::FileMetadata;
+use crate::file::list_file::ListFile;
+use crate::file::string_utils::{find_file_extension, shell_quote};
+use crate::file::temp_dir::TempDir;
+use std::error::Error;
+
+/// Streams decompressed content of a single entry to stdout, e.g. `--cat release.tar.gz conf/app.json | jq`.
+pub fn cat_path(archive: &FileMetadata, entry_path: &str) -> Result<(), Box<dyn Error>> {
+    if archive.is_directory {
+        return Err(OperationError::ArchiveIsDirectory.into());
+    }
+    let entries = archive_entry::read_entries(&archive.path)?;
+    let entry = archive_entry::find_entry(&entries, entry_path)?;
+    if entry.kind == ArchiveEntryKind::Directory {
+        return Err(OperationError::EntryIsDirectory(entry_path.to_owned()).into());
+    }
+    stream_command(&cat_cmd(&archive.path, &entry.path))
+}
+
+pub fn cat_cmd(archive: &str, entry: &str) -> String {
+    let is_zip = matches!(find_file_extension(archive), Ok(ext) if ext == ".zip");
+    let quoted_archive = shell_quote(archive);
+    match is_zip {
+        true => format!(
+            "unzip -p {quoted_archive} {}",
+            shell_quote(&unpacker::escape_wildcards(entry))
+        ),
+        false => format!("tar -xOf {quoted_archive} -- {}", shell_quote(entry)),
+    }
+}
+
+/// Runs `entry_cmd` on the content of every file entry, passed on stdin, reading the archive once:
+/// `tar` streams the entries into the command one after another, a ZIP archive has them unpacked
+/// to a temporary directory by a single `unzip`. Returns the printed lines with the stored path
+/// of the entry they were printed for.
+pub fn entries_output(
+    archive: &str,
+    entries: &[ArchiveEntry],
+    entry_cmd: &str,
+) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let files = entries
+        .iter()
+        .filter(|entry| {
+            entry.kind == ArchiveEntryKind::File && path_safety::normalize(&entry.path).is_ok()
+        })
+        .cloned()
+        .collect::<Vec<ArchiveEntry>>();
+    if files.is_empty() {
+        return Ok(Vec::new());
+    }
+    let members = files
+        .iter()
+        .map(|entry| entry.path.to_owned())
+        .collect::<Vec<String>>();
+    let list = ListFile::new(&members)?;
+    // NUL is part of neither a path nor a line of text
+    let labelled_cmd = format!(
+        "{{ {entry_cmd}; }} | while IFS= read -r line; do printf '%s\\000%s\\n' \"$ENTRY_PATH\" \"$line\"; done"
+    );
+    let is_zip = matches!(find_file_extension(archive), Ok(ext) if ext == ".zip");
+    let unpacked_dir;
+    let cmd = match is_zip {
+        true => {
+            limits::check_entries(archive, &files)?;
+            unpacked_dir = TempDir::new("entries")?;
+            unpacker::extract_members(archive, &unpacked_dir.to_string_path(), &files)?;
+            let script = format!("for ENTRY_PATH; do ({labelled_cmd}) < \"$ENTRY_PATH\"; done");
+            format!(
+                "cd {} && xargs -0 -r sh -c {} sh < {}",
+                shell_quote(&unpacked_dir.to_string_path()),
+                shell_quote(&script),
+                list.path()
+            )
+        }
+        false => format!(
+            "tar -xf {} --no-recursion --null -T {} --to-command={}",
+            shell_quote(archive),
+            list.path(),
+            shell_quote(&format!("ENTRY_PATH=\"$TAR_FILENAME\"; {labelled_cmd}"))
+        ),
+    };
+    Ok(execute_cmd_get_lines(&cmd)
+        .into_iter()
+        .filter_map(|line| {
+            let (path, text) = line.split_once('\0')?;
+            Some((path.to_owned(), text.to_owned()))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::command_runner::{execute_cmd, execute_cmd_get_lines};
+    use crate::file::temp_dir::TempDir;
+    use std::fs;
+
+    #[test]
+    fn should_run_command_on_every_entry() {
+        let dir = TempDir::new("cat_test").unwrap();
+        let root = shell_quote(&dir.to_string_path());
+        execute_cmd(&format!(
+            "cd {root} && mkdir d && printf 'a\\nb\\n' > d/a && printf ' c' > 'd/it'\\''s' \\
+            && zip -qr a.zip d && tar -czf a.tar.gz d"
+        ))
+        .unwrap();
+        for archive in ["a.zip", "a.tar.gz"] {
+            let archive = dir.path().join(archive).display().to_string();
+            let entries = archive_entry::read_entries(&archive).unwrap();
+            let mut output = entries_output(&archive, &entries, "cat; echo").unwrap();
+            output.sort();
+            assert_eq!(
+                output,
+                vec![
+                    ("d/a".to_owned(), String::new()),
+                    ("d/a".to_owned(), "a".to_owned()),
+                    ("d/a".to_owned(), "b".to_owned()),
+                    ("d/it's".to_owned(), " c".to_owned()),
+                ],
+                "{archive}"
+            );
+        }
+    }
+
+    #[test]
+    fn should_cat_entry_starting_with_dash() {
+        let dir = TempDir::new("cat_test").unwrap();
+        fs::write(dir.path().join("-b.txt"), "dash").unwrap();
+        let root = shell_quote(&dir.to_string_path());
+        execute_cmd(&format!(
+            "cd {root} && tar -cf a.tar -- -b.txt && zip -q a.zip -- -b.txt"
+        ))
+        .unwrap();
+        for archive in ["a.tar", "a.zip"] {
+            let archive = dir.path().join(archive).display().to_string();
+            assert_eq!(
+                execute_cmd_get_lines(&cat_cmd(&archive, "-b.txt")),
+                vec!["dash"],
+                "{archive}"
+            );
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use crate::archive::unpacker;
+use crate::cmd::command_runner::execute_cmd_get_lines;
+use crate::error::OperationError;
+use crate::file::file_metadata::FileMetadata;
+use crate::file::string_utils::shell_quote;
+use crate::file::tree_snapshot;
+use crate::file::tree_snapshot::{EntryKind, TreeEntry};
+use crate::output;
+use crate::output::Json;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+const TEXT_DETECTION_BYTES: u64 = 8000;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct DiffReport {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+    pub permission_changed: Vec<(String, u32, u32)>,
+}
+
+impl DiffReport {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.modified.is_empty()
+            && self.permission_changed.is_empty()
+    }
+}
+
+/// Shows what extracting the archive into the directory would change: entries missing on disk are `added`,
+/// entries present only on disk are `removed`.
+pub fn diff_archive_with_dir(
+    archive: &FileMetadata,
+    dir: &FileMetadata,
+    porcelain: bool,
+) -> Result<(), Box<dyn Error>> {
+    if archive.is_directory {
+        return Err(OperationError::ArchiveIsDirectory.into());
+    }
+    if !dir.is_directory {
+        return Err(OperationError::NotADirectory(dir.path.to_owned()).into());
+    }
+
+    let unpacked = unpacker::unpack_to_temp(&archive.path)?;
+    let archive_tree = tree_snapshot::snapshot(unpacked.path())?;
+    let dir_tree = tree_snapshot::snapshot(Path::new(&dir.path))?;
+
+    let report = compare(&archive_tree, &dir_tree);
+    print_report(&report, porcelain);
+    Ok(())
+}
+
+/// Compares entries of two archives of any supported formats by their content hashes.
+/// With `text_diff`, a unified diff is printed for every modified text entry.
+pub fn diff_archives(
+    old: &FileMetadata,
+    new: &FileMetadata,
+    porcelain: bool,
+    text_diff: bool,
+) -> Result<(), Box<dyn Error>> {
+    if old.is_directory || new.is_directory {
+        return Err(OperationError::ArchiveIsDirectory.into());
+    }
+
+    let old_unpacked = unpacker::unpack_to_temp(&old.path)?;
+    let new_unpacked = unpacker::unpack_to_temp(&new.path)?;
+    let old_tree = tree_snapshot::snapshot(old_unpacked.path())?;
+    let new_tree = tree_snapshot::snapshot(new_unpacked.path())?;
+
+    let report = compare(&new_tree, &old_tree);
+    print_report(&report, porcelain);
+
+    if text_diff {
+        for path in &report.modified {
+            let old_file = old_unpacked.path().join(path);
+            let new_file = new_unpacked.path().join(path);
+            if is_text_file(&old_file) && is_text_file(&new_file) {
+                let old_label = format!("{}:{path}", old.path);
+                let new_label = format!("{}:{path}", new.path);
+                print_unified_diff(&old_file, &new_file, &old_label, &new_label);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Files without NUL bytes at the beginning are treated as text, the same heuristic `diff` and `grep` use.
+fn is_text_file(path: &Path) -> bool {
+    let mut buffer = Vec::new();
+    match File::open(path) {
+        Ok(file) => match file.take(TEXT_DETECTION_BYTES).read_to_end(&mut buffer) {
+            Ok(_) => !buffer.contains(&0),
+            Err(_) => false,
+        },
+        Err(_) => false,
+    }
+}
+
+fn print_unified_diff(old_file: &Path, new_file: &Path, old_label: &str, new_label: &str) {
+    let cmd = format!(
+        "diff -u --label {} --label {} {} {}",
+        shell_quote(old_label),
+        shell_quote(new_label),
+        shell_quote(&old_file.display().to_string()),
+        shell_quote(&new_file.display().to_string())
+    );
+    for line in execute_cmd_get_lines(&cmd) {
+        output::status(&line);
+    }
+}
+
+pub fn compare(new: &BTreeMap<String, TreeEntry>, old: &BTreeMap<String, TreeEntry>) -> DiffReport {
+    let mut report = DiffReport::default();
+    for (path, new_entry) in new {
+        match old.get(path) {
+            None => report.added.push(path.to_owned()),
+            Some(old_entry) if is_modified(new_entry, old_entry) => {
+                report.modified.push(path.to_owned())
+            }
+            Some(old_entry) => {
+                if new_entry.kind != EntryKind::Symlink && new_entry.mode != old_entry.mode {
+                    report.permission_changed.push((
+                        path.to_owned(),
+                        old_entry.mode,
+                        new_entry.mode,
+                    ));
+                }
+            }
+        }
+    }
+    report.removed = old
+        .keys()
+        .filter(|path| !new.contains_key(*path))
+        .cloned()
+        .collect();
+    report
+}
+
+#[inline]
+fn is_modified(new: &TreeEntry, old: &TreeEntry) -> bool {
+    match (new.kind, old.kind) {
+        (EntryKind::File, EntryKind::File) => new.size != old.size || new.hash != old.hash,
+        (EntryKind::Symlink, EntryKind::Symlink) => new.link_target != old.link_target,
+        (new_kind, old_kind) => new_kind != old_kind,
+    }
+}
+
+/// Human-readable report, or one tab-separated `<change>\t<path>` line per entry for scripts.
+pub fn print_report(report: &DiffReport, porcelain: bool) {
+    if output::is_machine_readable() {
+        print_json_report(report);
+        return;
+    }
+    let changes = [
+        ("added", "+", &report.added),
+        ("removed", "-", &report.removed),
+        ("modified", "~", &report.modified),
+    ];
+    for (change, symbol, paths) in changes {
+        for path in paths {
+            match porcelain {
+                true => println!("{change}\t{path}"),
+                false => println!("{symbol} {path}"),
+            }
+        }
+    }
+    for (path, old_mode, new_mode) in &report.permission_changed {
+        match porcelain {
+            true => println!("mode\t{path}\t{old_mode:o}\t{new_mode:o}"),
+            false => println!("! {path} (mode {old_mode:o} -> {new_mode:o})"),
+        }
+    }
+
+    if !porcelain {
+        match report.is_empty() {
+            true => println!("No differences found."),
+            false => println!(
+                "{} added, {} removed, {} modified, {} with changed permissions.",
+                report.added.len(),
+                report.removed.len(),
+                report.modified.len(),
+                report.permission_changed.len()
+            ),
+        }
+    }
+}
+
+fn print_json_report(report: &DiffReport) {
+    let changes = [
+        ("added", &report.added),
+        ("removed", &report.removed),
+        ("modified", &report.modified),
+    ];
+    let mut items = changes
+        .iter()
+        .flat_map(|(change, paths)| {
+            paths.iter().map(|path| {
+                Json::object(vec![
+                    ("change", Json::from(*change)),
+                    ("path", Json::from(path.as_str())),
+                ])
+            })
+        })
+        .collect::<Vec<Json>>();
+    for (path, old_mode, new_mode) in &report.permission_changed {
+        items.push(Json::object(vec![
+            ("change", Json::from("mode")),
+            ("path", Json::from(path.as_str())),
+            ("old_mode", Json::from(format!("{old_mode:o}"))),
+            ("new_mode", Json::from(format!("{new_mode:o}"))),
+        ]));
+    }
+    output::print_result("diff", vec![], "changes", items);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(size: u64, mode: u32, hash: &str) -> TreeEntry {
+        TreeEntry {
+            kind: EntryKind::File,
+            size,
+            mode,
+            hash: Some(hash.to_owned()),
+            link_target: None,
+        }
+    }
+
+    #[test]
+    fn should_compare_trees() {
+        let new = BTreeMap::from([
+            ("added.txt".to_owned(), file(1, 0o644, "a")),
+            ("same.txt".to_owned(), file(1, 0o644, "s")),
+            ("changed.txt".to_owned(), file(1, 0o644, "new")),
+            ("script.sh".to_owned(), file(1, 0o755, "x")),
+        ]);
+        let old = BTreeMap::from([
+            ("removed.txt".to_owned(), file(1, 0o644, "r")),
+            ("same.txt".to_owned(), file(1, 0o644, "s")),
+            ("changed.txt".to_owned(), file(1, 0o644, "old")),
+            ("script.sh".to_owned(), file(1, 0o644, "x")),
+        ]);
+
+        let report = compare(&new, &old);
+        assert_eq!(report.added, vec!["added.txt"]);
+        assert_eq!(report.removed, vec!["removed.txt"]);
+        assert_eq!(report.modified, vec!["changed.txt"]);
+        assert_eq!(
+            report.permission_changed,
+            vec![("script.sh".to_owned(), 0o644, 0o755)]
+        );
+    }
+
+    #[test]
+    fn should_detect_text_files() {
+        assert!(is_text_file(Path::new("./README.md")));
+        assert!(!is_text_file(Path::new(
+            "./resources/test/archives/resources_archive.zip"
+        )));
+    }
+
+    #[test]
+    fn should_find_no_differences() {
+        let tree = BTreeMap::from([("same.txt".to_owned(), file(1, 0o644, "s"))]);
+        assert!(compare(&tree, &tree).is_empty());
+    }
+}
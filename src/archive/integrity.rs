@@ -0,0 +1,35 @@
+use crate::cmd::command_runner::execute_cmd;
+use crate::error::OperationError;
+use crate::file::file_metadata::FileMetadata;
+use crate::file::string_utils::{find_file_extension, shell_quote};
+use crate::output;
+use crate::output::Json;
+use std::error::Error;
+
+/// Reads the whole archive, decompressing every entry, without writing anything to disk.
+pub fn test_path(archive: &FileMetadata) -> Result<(), Box<dyn Error>> {
+    if archive.is_directory {
+        return Err(OperationError::ArchiveIsDirectory.into());
+    }
+    let path = &archive.path;
+    let is_zip = matches!(find_file_extension(path), Ok(ext) if ext == ".zip");
+    let quoted = shell_quote(path);
+    let cmd = match is_zip {
+        true => format!("unzip -tq {quoted}"),
+        false => format!("tar -tf {quoted} > /dev/null"),
+    };
+
+    if let Err(error) = execute_cmd(&cmd) {
+        return Err(OperationError::ArchiveIsCorrupted(path.to_owned(), error.to_string()).into());
+    }
+    match output::is_machine_readable() {
+        true => output::print_result(
+            "test",
+            vec![("archive", Json::from(path.as_str()))],
+            "",
+            vec![],
+        ),
+        false => println!("Archive '{path}' is OK."),
+    }
+    Ok(())
+}
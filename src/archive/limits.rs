@@ -0,0 +1,202 @@
+use crate::archive::archive_entry::ArchiveEntry;
+use crate::error::OperationError;
+use std::error::Error;
+use std::fs;
+use std::sync::OnceLock;
+
+static LIMITS: OnceLock<ExtractionLimits> = OnceLock::new();
+
+/// Smaller entries are not checked for their compression ratio - a few KB of zeros is harmless.
+const RATIO_MIN_SIZE: u64 = 1024 * 1024;
+
+/// Protection against decompression bombs, checked against the entry listing before anything is extracted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExtractionLimits {
+    pub max_bytes: u64,
+    pub max_entries: usize,
+    /// Uncompressed size divided by compressed size, of a single entry or of the whole archive
+    /// when the format compresses it as a whole.
+    pub max_ratio: f64,
+    /// How deep archives inside archives are followed, e.g. by nested paths and search.
+    pub max_depth: usize,
+}
+
+impl Default for ExtractionLimits {
+    fn default() -> Self {
+        ExtractionLimits {
+            max_bytes: 10 * 1024 * 1024 * 1024,
+            max_entries: 100_000,
+            max_ratio: 100.0,
+            max_depth: 5,
+        }
+    }
+}
+
+/// Set once, from the command line options; later calls are ignored.
+pub fn set_limits(limits: ExtractionLimits) {
+    let _ = LIMITS.set(limits);
+}
+
+#[inline]
+pub fn limits() -> ExtractionLimits {
+    LIMITS.get().copied().unwrap_or_default()
+}
+
+/// Size in bytes with optional `K`, `M` or `G` suffix, e.g. `512M`.
+pub fn parse_size(option: &str, value: &str) -> Result<u64, Box<dyn Error>> {
+    let invalid = || OperationError::InvalidArgument(format!("{option} {value}"));
+    let (number, multiplier) = match value.to_ascii_uppercase().chars().last() {
+        Some('K') => (&value[..value.len() - 1], 1024),
+        Some('M') => (&value[..value.len() - 1], 1024 * 1024),
+        Some('G') => (&value[..value.len() - 1], 1024 * 1024 * 1024),
+        _ => (value, 1),
+    };
+    let number = number.parse::<u64>().map_err(|_| invalid())?;
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| invalid().into())
+}
+
+pub fn check_entries(archive: &str, entries: &[ArchiveEntry]) -> Result<(), Box<dyn Error>> {
+    let limits = limits();
+    if entries.len() > limits.max_entries {
+        let details = format!(
+            "'{archive}' has {} entries, at most {} are allowed",
+            entries.len(),
+            limits.max_entries
+        );
+        return Err(
+            OperationError::ExtractionLimitExceeded("--max-entries".to_owned(), details).into(),
+        );
+    }
+
+    let total_size = entries.iter().map(|entry| entry.size).sum::<u64>();
+    if total_size > limits.max_bytes {
+        let details = format!(
+            "'{archive}' expands to {total_size} bytes, at most {} are allowed",
+            limits.max_bytes
+        );
+        return Err(
+            OperationError::ExtractionLimitExceeded("--max-bytes".to_owned(), details).into(),
+        );
+    }
+
+    for entry in entries.iter().filter(|entry| entry.size >= RATIO_MIN_SIZE) {
+        if let Some(compressed_size) = entry.compressed_size {
+            check_ratio(
+                &format!("entry '{}'", entry.path),
+                entry.size,
+                compressed_size,
+                limits,
+            )?;
+        }
+    }
+    if total_size >= RATIO_MIN_SIZE && entries.iter().all(|entry| entry.compressed_size.is_none()) {
+        let archive_size = fs::metadata(archive)?.len();
+        check_ratio(&format!("'{archive}'"), total_size, archive_size, limits)?;
+    }
+    Ok(())
+}
+
+fn check_ratio(
+    name: &str,
+    size: u64,
+    compressed_size: u64,
+    limits: ExtractionLimits,
+) -> Result<(), Box<dyn Error>> {
+    let ratio = size as f64 / compressed_size.max(1) as f64;
+    match ratio > limits.max_ratio {
+        true => {
+            let details = format!(
+                "{name} expands {ratio:.0} times, at most {} times is allowed",
+                limits.max_ratio
+            );
+            Err(OperationError::ExtractionLimitExceeded("--max-ratio".to_owned(), details).into())
+        }
+        false => Ok(()),
+    }
+}
+
+/// Runs the extracting command with no file allowed to grow past the largest checked entry, so an archive
+/// listing smaller sizes than it stores can not fill the disk with a single entry. `tar` reads exactly the
+/// listed size of a member, `unzip` writes whatever the compressed data expands to.
+pub fn capped_cmd(entries: &[ArchiveEntry], cmd: &str) -> String {
+    let largest = entries.iter().map(|entry| entry.size).max().unwrap_or(0);
+    // in 512 byte blocks; the tool is killed writing past it
+    format!("(ulimit -f {} && {cmd})", largest.div_ceil(512).max(1))
+}
+
+/// `depth` of the archive about to be extracted, the one on disk being 0.
+pub fn check_depth(location: &str, depth: usize) -> Result<(), Box<dyn Error>> {
+    let max_depth = limits().max_depth;
+    match depth > max_depth {
+        true => {
+            let details = format!(
+                "'{location}' is nested {depth} levels deep, at most {max_depth} are allowed"
+            );
+            Err(OperationError::ExtractionLimitExceeded("--max-depth".to_owned(), details).into())
+        }
+        false => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::archive_entry::ArchiveEntryKind;
+    use crate::archive::archive_entry::test_entry;
+    use crate::cmd::command_runner::execute_cmd;
+    use crate::file::string_utils::shell_quote;
+    use crate::file::temp_dir::TempDir;
+
+    fn entry(size: u64, compressed_size: u64) -> ArchiveEntry {
+        ArchiveEntry {
+            size,
+            compressed_size: Some(compressed_size),
+            ..test_entry("bomb.bin", ArchiveEntryKind::File)
+        }
+    }
+
+    #[test]
+    fn should_parse_size() {
+        assert_eq!(parse_size("--max-bytes", "512").unwrap(), 512);
+        assert_eq!(parse_size("--max-bytes", "2k").unwrap(), 2048);
+        assert_eq!(parse_size("--max-bytes", "1G").unwrap(), 1024 * 1024 * 1024);
+        assert!(parse_size("--max-bytes", "G").is_err());
+        assert!(parse_size("--max-bytes", "-1").is_err());
+    }
+
+    #[test]
+    fn should_cap_written_files_at_largest_entry() {
+        let dir = TempDir::new("limits_test").unwrap();
+        let file = shell_quote(&dir.path().join("out").display().to_string());
+        let write = format!("head -c 4096 /dev/zero > {file}");
+        assert!(execute_cmd(&capped_cmd(&[entry(4096, 1)], &write)).is_ok());
+        assert!(execute_cmd(&capped_cmd(&[entry(1024, 1)], &write)).is_err());
+        assert_eq!(fs::metadata(dir.path().join("out")).unwrap().len(), 1024);
+    }
+
+    #[test]
+    fn should_check_default_limits() {
+        assert!(check_entries("ok.zip", &[entry(2 * RATIO_MIN_SIZE, RATIO_MIN_SIZE)]).is_ok());
+        // small entries may compress well
+        assert!(check_entries("ok.zip", &[entry(RATIO_MIN_SIZE - 1, 1)]).is_ok());
+
+        let error = check_entries(
+            "bomb.zip",
+            &[entry(4 * 1024 * 1024 * 1024, 4 * 1024 * 1024)],
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("--max-ratio"));
+
+        let error = check_entries(
+            "big.zip",
+            &[entry(20 * 1024 * 1024 * 1024, 10 * 1024 * 1024 * 1024)],
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("--max-bytes"));
+
+        assert!(check_depth("a.zip//b.zip", 1).is_ok());
+        assert!(check_depth("a.zip//b.zip", 6).is_err());
+    }
+}
@@ -0,0 +1,216 @@
+use crate::archive::archive_entry;
+use crate::archive::archive_entry::ArchiveEntry;
+use crate::cmd::command_runner::execute_cmd_get_lines;
+use crate::error::OperationError;
+use crate::file::file_metadata::FileMetadata;
+use crate::file::string_utils::glob_matches;
+use crate::input_handler;
+use crate::output;
+use crate::output::Json;
+use std::cmp::{Ordering, Reverse};
+use std::error::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Name,
+    Size,
+    Compressed,
+    Ratio,
+    Mtime,
+}
+
+pub fn parse_sort_key(value: &str) -> Result<SortKey, Box<dyn Error>> {
+    let key = match value {
+        "name" | "path" => SortKey::Name,
+        "size" => SortKey::Size,
+        "compressed" => SortKey::Compressed,
+        "ratio" => SortKey::Ratio,
+        "mtime" | "time" => SortKey::Mtime,
+        _ => return Err(OperationError::InvalidArgument(format!("--sort {value}")).into()),
+    };
+    Ok(key)
+}
+
+pub fn list() -> Result<(), Box<dyn Error>> {
+    let archive = input_handler::read_path_to_archive()?;
+    let filters =
+        input_handler::read_optional("Show only entries matching glob (leave empty for all):");
+    list_archive(&archive, &Vec::from_iter(filters), None)
+}
+
+pub fn list_path(
+    archive: &FileMetadata,
+    filters: &[String],
+    sort: Option<SortKey>,
+) -> Result<(), Box<dyn Error>> {
+    if archive.is_directory {
+        return Err(OperationError::ArchiveIsDirectory.into());
+    }
+    list_archive(&archive.path, filters, sort)
+}
+
+fn list_archive(
+    archive: &str,
+    filters: &[String],
+    sort: Option<SortKey>,
+) -> Result<(), Box<dyn Error>> {
+    let entries = archive_entry::read_entries(archive)?;
+    let entries = filter_and_sort(entries, filters, sort);
+    match output::is_machine_readable() {
+        true => output::print_result(
+            "list",
+            vec![("archive", Json::from(archive))],
+            "entries",
+            entries.iter().map(|entry| entry.to_json()).collect(),
+        ),
+        false => print_entries(&entries),
+    }
+    Ok(())
+}
+
+/// Keeps entries matching any of the globs (all when there are none); numeric keys put the largest first.
+pub fn filter_and_sort(
+    entries: Vec<ArchiveEntry>,
+    filters: &[String],
+    sort: Option<SortKey>,
+) -> Vec<ArchiveEntry> {
+    let mut entries = entries
+        .into_iter()
+        .filter(|entry| {
+            filters.is_empty() || filters.iter().any(|glob| glob_matches(glob, &entry.path))
+        })
+        .collect::<Vec<ArchiveEntry>>();
+
+    match sort {
+        None => {}
+        Some(SortKey::Name) => entries.sort_by(|a, b| a.normalized_path().cmp(b.normalized_path())),
+        Some(SortKey::Size) => entries.sort_by_key(|entry| Reverse(entry.size)),
+        Some(SortKey::Compressed) => entries.sort_by_key(|entry| Reverse(entry.compressed_size)),
+        Some(SortKey::Ratio) => {
+            entries.sort_by(|a, b| b.ratio().partial_cmp(&a.ratio()).unwrap_or(Ordering::Equal))
+        }
+        Some(SortKey::Mtime) => {
+            let today = today();
+            entries.sort_by_cached_key(|entry| {
+                Reverse(archive_entry::parse_mtime(&entry.mtime, today))
+            })
+        }
+    }
+    entries
+}
+
+/// Local date as `(year, month, day)`, which listings without a year are relative to.
+fn today() -> (u32, u32, u32) {
+    let date = execute_cmd_get_lines("date +%Y-%m-%d")
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+    let mut parts = date.split('-').map(|part| part.parse::<u32>().unwrap_or(0));
+    (
+        parts.next().unwrap_or(0),
+        parts.next().unwrap_or(0),
+        parts.next().unwrap_or(0),
+    )
+}
+
+fn print_entries(entries: &[ArchiveEntry]) {
+    println!(
+        "{:<10} {:>12} {:>12} {:>6} {:<19} {:<3} Path",
+        "Mode", "Size", "Compressed", "Ratio", "Modified", "Enc"
+    );
+    for entry in entries {
+        let compressed = match entry.compressed_size {
+            Some(size) => size.to_string(),
+            None => "-".to_owned(),
+        };
+        let path = match &entry.link_target {
+            Some(target) => format!("{} -> {target}", entry.path),
+            None => entry.path.to_owned(),
+        };
+        println!(
+            "{:<10} {:>12} {:>12} {:>6} {:<19} {:<3} {path}",
+            entry.mode,
+            entry.size,
+            compressed,
+            format_ratio(entry.ratio()),
+            entry.mtime,
+            if entry.encrypted { "yes" } else { "no" },
+        );
+    }
+
+    let total_size = entries.iter().map(|entry| entry.size).sum::<u64>();
+    println!(
+        "{} entries, {total_size} bytes uncompressed.",
+        entries.len()
+    );
+}
+
+#[inline]
+pub fn format_ratio(ratio: Option<f64>) -> String {
+    match ratio {
+        Some(ratio) => format!("{:.1}%", ratio * 100.0),
+        None => "-".to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::archive_entry::ArchiveEntryKind;
+    use crate::archive::archive_entry::test_entry;
+
+    fn entry(path: &str, size: u64, compressed_size: Option<u64>) -> ArchiveEntry {
+        ArchiveEntry {
+            size,
+            compressed_size,
+            ..test_entry(path, ArchiveEntryKind::File)
+        }
+    }
+
+    #[test]
+    fn should_filter_and_sort_entries() {
+        let entries = vec![
+            entry("./a.txt", 10, Some(5)),
+            entry("./b.png", 30, Some(29)),
+            entry("./c.txt", 20, Some(2)),
+        ];
+
+        let by_size = filter_and_sort(entries.clone(), &[], Some(SortKey::Size));
+        let paths = by_size
+            .iter()
+            .map(|e| e.path.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(paths, vec!["./b.png", "./c.txt", "./a.txt"]);
+
+        let txt_by_ratio = filter_and_sort(entries, &["*.txt".to_owned()], Some(SortKey::Ratio));
+        let paths = txt_by_ratio
+            .iter()
+            .map(|e| e.path.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(paths, vec!["./c.txt", "./a.txt"]);
+    }
+
+    #[test]
+    fn should_sort_by_parsed_mtime() {
+        let mut entries = vec![
+            entry("./zip.txt", 1, Some(1)),
+            entry("./bsdtar.txt", 1, None),
+            entry("./gnu.txt", 1, None),
+        ];
+        entries[0].mtime = "2001-01-01 00:00:00".to_owned();
+        entries[1].mtime = "Jan 1 2000".to_owned();
+        entries[2].mtime = "2002-01-01 00:00".to_owned();
+        let by_mtime = filter_and_sort(entries, &[], Some(SortKey::Mtime));
+        let paths = by_mtime
+            .iter()
+            .map(|e| e.path.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(paths, vec!["./gnu.txt", "./zip.txt", "./bsdtar.txt"]);
+    }
+
+    #[test]
+    fn should_parse_sort_key() {
+        assert_eq!(parse_sort_key("size").unwrap(), SortKey::Size);
+        assert!(parse_sort_key("colour").is_err());
+    }
+}
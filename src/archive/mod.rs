@@ -0,0 +1,12 @@
+pub mod archive_entry;
+pub mod cat;
+pub mod diff;
+pub mod integrity;
+pub mod limits;
+pub mod list;
+pub mod nested;
+pub mod path_safety;
+pub mod search;
+pub mod selection;
+pub mod stats;
+pub mod unpacker;
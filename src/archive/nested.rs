@@ -0,0 +1,245 @@
+use crate::archive::archive_entry::ArchiveEntry;
+use crate::archive::{archive_entry, limits, path_safety, unpacker};
+use crate::cmd::command_builder::is_archive_path;
+use crate::cmd::command_runner::run_command;
+use crate::error::OperationError;
+use crate::file::file_metadata;
+use crate::file::string_utils::{find_file_extension, shell_quote};
+use crate::file::temp_dir::TempDir;
+use crate::output;
+use crate::output::Json;
+use std::error::Error;
+use std::fs;
+
+/// Separator of nested archives in paths, e.g. `bundle.tar.gz//release.zip//conf/app.yml`.
+pub const NESTED_ARCHIVE_SEPARATOR: &str = "//";
+
+/// Path through nested archives: the archive on disk, the archives nested in it and an optional entry
+/// of the innermost one.
+#[derive(Debug, PartialEq)]
+pub struct NestedPath {
+    pub archive: String,
+    pub nested_archives: Vec<String>,
+    pub entry: Option<String>,
+}
+
+/// Innermost archive of a nested path, extracted to a temporary directory that lives as long as this.
+#[derive(Debug)]
+pub struct ResolvedPath {
+    pub archive: String,
+    pub entry: Option<String>,
+    _temp_dirs: Vec<TempDir>,
+}
+
+/// `None` for regular paths - only a path starting with an archive followed by the separator is nested.
+pub fn parse(path: &str) -> Option<NestedPath> {
+    let mut segments = path.split(NESTED_ARCHIVE_SEPARATOR);
+    let archive = segments.next()?;
+    if !is_archive_path(archive) || !path.contains(NESTED_ARCHIVE_SEPARATOR) {
+        return None;
+    }
+    let mut segments = segments
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| segment.to_owned())
+        .collect::<Vec<String>>();
+    let entry = match segments.last() {
+        Some(last) if !is_archive_path(last) => segments.pop(),
+        _ => None,
+    };
+    Some(NestedPath {
+        archive: archive.to_owned(),
+        nested_archives: segments,
+        entry,
+    })
+}
+
+/// Extracts every nested archive of the path, one level at a time; regular paths are returned as they are.
+pub fn resolve(path: &str) -> Result<ResolvedPath, Box<dyn Error>> {
+    let Some(nested_path) = parse(path) else {
+        return Ok(ResolvedPath {
+            archive: path.to_owned(),
+            entry: None,
+            _temp_dirs: Vec::new(),
+        });
+    };
+    validate_segments(&nested_path)?;
+
+    let mut archive = nested_path.archive;
+    let mut temp_dirs = Vec::new();
+    for (depth, member) in nested_path.nested_archives.iter().enumerate() {
+        limits::check_depth(path, depth + 1)?;
+        let entries = archive_entry::read_entries(&archive)?;
+        let entry = archive_entry::find_entry(&entries, member)?;
+        path_safety::safe_entries(&archive, std::slice::from_ref(entry))?;
+        limits::check_entries(&archive, std::slice::from_ref(entry))?;
+        let dir = TempDir::new("nested")?;
+        unpacker::extract_members(&archive, &dir.to_string_path(), std::slice::from_ref(entry))?;
+        archive = dir.path().join(&entry.path).display().to_string();
+        temp_dirs.push(dir);
+    }
+    Ok(ResolvedPath {
+        archive,
+        entry: nested_path.entry,
+        _temp_dirs: temp_dirs,
+    })
+}
+
+/// Every segment except the last one has to be an archive, e.g. `bundle.tar.gz//conf//app.yml` is rejected.
+fn validate_segments(nested_path: &NestedPath) -> Result<(), Box<dyn Error>> {
+    match nested_path
+        .nested_archives
+        .iter()
+        .find(|segment| !is_archive_path(segment))
+    {
+        Some(segment) => Err(OperationError::NotAnArchive(segment.to_owned()).into()),
+        None => Ok(()),
+    }
+}
+
+/// Adds the files to the archive; for a nested path every enclosing archive is rebuilt with the updated one.
+pub fn add_to_path(path: &str, files: &[String]) -> Result<(), Box<dyn Error>> {
+    if files.is_empty() {
+        return Err(OperationError::MissingOperand("file to add".to_owned()).into());
+    }
+    for file in files {
+        file_metadata::get_file_metadata(file)?;
+    }
+    let Some(nested_path) = parse(path) else {
+        let archive = file_metadata::get_file_metadata(path)?;
+        if archive.is_directory {
+            return Err(OperationError::ArchiveIsDirectory.into());
+        }
+        run_command(&add_files_cmd(&archive.path, files))?;
+        print_added(path, files);
+        return Ok(());
+    };
+    validate_segments(&nested_path)?;
+    if let Some(entry) = nested_path.entry {
+        return Err(OperationError::NotAnArchive(entry).into());
+    }
+
+    // each level is unpacked whole, so it can be packed again around the updated archive
+    let mut levels: Vec<(String, TempDir, Vec<ArchiveEntry>)> = Vec::new();
+    let mut archive = nested_path.archive;
+    for (depth, member) in nested_path.nested_archives.iter().enumerate() {
+        limits::check_depth(path, depth + 1)?;
+        let entries = archive_entry::read_entries(&archive)?;
+        let stored_path = archive_entry::find_entry(&entries, member)?.path.to_owned();
+        let dir = unpacker::unpack_to_temp(&archive)?;
+        let inner_archive = dir.path().join(&stored_path).display().to_string();
+        levels.push((archive, dir, entries));
+        archive = inner_archive;
+    }
+
+    let mut cmds = vec![add_files_cmd(&archive, files)];
+    for (archive, dir, entries) in levels.iter().rev() {
+        cmds.push(repack_cmd(archive, &dir.to_string_path(), entries)?);
+    }
+    run_command(&cmds.join(" && "))?;
+    print_added(path, files);
+    Ok(())
+}
+
+fn print_added(path: &str, files: &[String]) {
+    if output::is_machine_readable() {
+        let files = files.iter().map(|file| Json::from(file.as_str())).collect();
+        output::print_result("add", vec![("archive", Json::from(path))], "files", files);
+    }
+}
+
+pub fn add_files_cmd(archive: &str, files: &[String]) -> String {
+    let is_zip = matches!(find_file_extension(archive), Ok(ext) if ext == ".zip");
+    let files = files
+        .iter()
+        .map(|file| shell_quote(file))
+        .collect::<Vec<String>>()
+        .join(" ");
+    let archive = shell_quote(archive);
+    match is_zip {
+        true => format!("zip -ur {archive} {files}"),
+        false => format!("tar -rv --append --file={archive} {files}"),
+    }
+}
+
+/// Packs the unpacked `dir` back into the archive, keeping the original entries and their order.
+fn repack_cmd(
+    archive: &str,
+    dir: &str,
+    entries: &[ArchiveEntry],
+) -> Result<String, Box<dyn Error>> {
+    let extension = find_file_extension(archive)?;
+    // the new archive replaces the old one only when it was packed completely
+    let partial = shell_quote(&format!(
+        "{}.partial{extension}",
+        fs::canonicalize(archive)?.display()
+    ));
+    let members = entries
+        .iter()
+        .map(|entry| shell_quote(&entry.path))
+        .collect::<Vec<String>>()
+        .join(" ");
+    let dir = shell_quote(dir);
+    let pack_cmd = match extension.as_str() {
+        ".zip" => format!("(cd {dir} && zip -q -y {partial} {members})"),
+        _ => format!("tar -caf {partial} -C {dir} --no-recursion -- {members}"),
+    };
+    Ok(format!(
+        "{pack_cmd} && mv {partial} {}",
+        shell_quote(archive)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::command_runner::execute_cmd;
+
+    #[test]
+    fn should_parse_nested_path() {
+        assert_eq!(
+            parse("./bundle.tar.gz//release.zip//conf/app.yml"),
+            Some(NestedPath {
+                archive: "./bundle.tar.gz".to_owned(),
+                nested_archives: vec!["release.zip".to_owned()],
+                entry: Some("conf/app.yml".to_owned()),
+            })
+        );
+        assert_eq!(
+            parse("bundle.tar.gz//release.zip//"),
+            Some(NestedPath {
+                archive: "bundle.tar.gz".to_owned(),
+                nested_archives: vec!["release.zip".to_owned()],
+                entry: None,
+            })
+        );
+        assert_eq!(parse("./my//dir/archive.zip"), None);
+        assert_eq!(parse("./bundle.tar.gz"), None);
+    }
+
+    #[test]
+    fn should_reject_entries_which_are_not_archives() {
+        let nested_path = parse("bundle.tar.gz//conf//app.yml").unwrap();
+        assert!(validate_segments(&nested_path).is_err());
+    }
+
+    #[test]
+    fn should_resolve_nested_path() {
+        let outer = TempDir::new("nested_test").unwrap();
+        let outer_archive = outer.path().join("outer.tar").display().to_string();
+        let cmd = format!(
+            "tar -cf {} -C ./resources/test/archives resources_archive.zip",
+            shell_quote(&outer_archive)
+        );
+        execute_cmd(&cmd).unwrap();
+
+        let resolved = resolve(&format!(
+            "{outer_archive}//resources_archive.zip//resources/test/test1.txt"
+        ))
+        .unwrap();
+        assert!(resolved.archive.ends_with("/resources_archive.zip"));
+        assert_eq!(resolved.entry.as_deref(), Some("resources/test/test1.txt"));
+        assert!(archive_entry::read_entries(&resolved.archive).is_ok());
+
+        assert!(resolve(&format!("{outer_archive}//missing.zip")).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use crate::archive::archive_entry::{ArchiveEntry, ArchiveEntryKind};
+use crate::archive::cat;
+use crate::cmd::command_runner::execute_cmd_get_lines;
+use crate::error::OperationError;
+use crate::output;
+use std::error::Error;
+
+/// Why an entry would be written outside the extraction directory.
+#[derive(Debug, PartialEq, Eq)]
+pub enum UnsafePath {
+    Absolute,
+    DrivePrefix,
+    ParentTraversal,
+    LinkOutsideDestination(String),
+}
+
+impl UnsafePath {
+    pub fn reason(&self) -> String {
+        match self {
+            UnsafePath::Absolute => "absolute path".to_owned(),
+            UnsafePath::DrivePrefix => "path with drive prefix".to_owned(),
+            UnsafePath::ParentTraversal => "path leaves the destination through '..'".to_owned(),
+            UnsafePath::LinkOutsideDestination(target) => {
+                format!("link to '{target}' points outside the destination")
+            }
+        }
+    }
+}
+
+/// Lexically normalized relative path, e.g. `./a/b/../c` -> `a/c`. Backslashes are treated as separators,
+/// as archives created on Windows may use them.
+pub fn normalize(path: &str) -> Result<String, UnsafePath> {
+    let path = path.replace('\\', "/");
+    if path.starts_with('/') {
+        return Err(UnsafePath::Absolute);
+    }
+    let bytes = path.as_bytes();
+    if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+        return Err(UnsafePath::DrivePrefix);
+    }
+
+    let mut components: Vec<&str> = Vec::new();
+    for component in path.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                if components.pop().is_none() {
+                    return Err(UnsafePath::ParentTraversal);
+                }
+            }
+            _ => components.push(component),
+        }
+    }
+    Ok(components.join("/"))
+}
+
+/// Symlinks are resolved against their own directory, hardlinks against the root of the archive.
+fn check_entry(entry: &ArchiveEntry, link_target: Option<&str>) -> Result<(), UnsafePath> {
+    let path = normalize(&entry.path)?;
+    let Some(target) = link_target else {
+        return Ok(());
+    };
+    let resolved = match entry.kind {
+        ArchiveEntryKind::Symlink => match (target.starts_with('/'), path.rsplit_once('/')) {
+            (true, _) => Err(UnsafePath::Absolute),
+            (false, Some((parent, _))) => normalize(&format!("{parent}/{target}")),
+            (false, None) => normalize(target),
+        },
+        _ => normalize(target),
+    };
+    match resolved {
+        Ok(_) => Ok(()),
+        Err(_) => Err(UnsafePath::LinkOutsideDestination(target.to_owned())),
+    }
+}
+
+/// Entries which stay inside the destination when extracted. The others are reported and left out;
+/// it is an error when nothing is left.
+pub fn safe_entries(
+    archive: &str,
+    entries: &[ArchiveEntry],
+) -> Result<Vec<ArchiveEntry>, Box<dyn Error>> {
+    let mut safe = Vec::new();
+    for entry in entries {
+        let link_target = match (&entry.link_target, entry.kind) {
+            (Some(target), _) => Some(target.to_owned()),
+            // zipinfo does not print targets, the content of a symlink entry is its target
+            (None, ArchiveEntryKind::Symlink) => {
+                execute_cmd_get_lines(&cat::cat_cmd(archive, &entry.path))
+                    .into_iter()
+                    .next()
+            }
+            _ => None,
+        };
+        match check_entry(entry, link_target.as_deref()) {
+            Ok(_) => safe.push(entry.clone()),
+            Err(unsafe_path) => output::status(&format!(
+                "Skipping unsafe entry '{}': {}.",
+                entry.path,
+                unsafe_path.reason()
+            )),
+        }
+    }
+    match safe.is_empty() && !entries.is_empty() {
+        true => Err(OperationError::UnsafeArchive(archive.to_owned()).into()),
+        false => Ok(safe),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::archive_entry::test_entry;
+
+    #[test]
+    fn should_normalize_paths() {
+        assert_eq!(
+            normalize("./conf/./a/../app.yml"),
+            Ok("conf/app.yml".to_owned())
+        );
+        assert_eq!(
+            normalize("../../etc/cron.d/x"),
+            Err(UnsafePath::ParentTraversal)
+        );
+        assert_eq!(normalize("a/../../x"), Err(UnsafePath::ParentTraversal));
+        assert_eq!(normalize("/etc/passwd"), Err(UnsafePath::Absolute));
+        assert_eq!(
+            normalize("C:\\Windows\\x.dll"),
+            Err(UnsafePath::DrivePrefix)
+        );
+        assert_eq!(normalize("..\\x"), Err(UnsafePath::ParentTraversal));
+    }
+
+    #[test]
+    fn should_reject_links_outside_destination() {
+        let symlink = test_entry("./conf/current", ArchiveEntryKind::Symlink);
+        assert!(check_entry(&symlink, Some("../releases/1.0")).is_ok());
+        assert_eq!(
+            check_entry(&symlink, Some("../../etc")),
+            Err(UnsafePath::LinkOutsideDestination("../../etc".to_owned()))
+        );
+        assert!(check_entry(&symlink, Some("/etc")).is_err());
+        let top_level = test_entry("current", ArchiveEntryKind::Symlink);
+        assert!(check_entry(&top_level, Some("releases/1.0")).is_ok());
+        assert!(check_entry(&top_level, Some("../1.0")).is_err());
+
+        let hardlink = test_entry("./conf/copy", ArchiveEntryKind::Hardlink);
+        assert!(check_entry(&hardlink, Some("./conf/app.yml")).is_ok());
+        assert!(check_entry(&hardlink, Some("../outside")).is_err());
+    }
+}
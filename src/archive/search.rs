@@ -0,0 +1,218 @@
+use crate::archive::archive_entry::{ArchiveEntry, ArchiveEntryKind};
+use crate::archive::nested::NESTED_ARCHIVE_SEPARATOR;
+use crate::archive::{archive_entry, cat, limits, path_safety, unpacker};
+use crate::cmd::command_builder::{find_archives_in_path, is_archive_path};
+use crate::cmd::command_runner::execute_cmd;
+use crate::error::OperationError;
+use crate::file::file_metadata;
+use crate::file::file_metadata::FileMetadata;
+use crate::file::string_utils::shell_quote;
+use crate::file::temp_dir::TempDir;
+use crate::output;
+use crate::output::Json;
+use std::collections::HashMap;
+use std::error::Error;
+
+#[derive(Debug, PartialEq)]
+pub struct SearchMatch {
+    pub archive: String,
+    pub entry: String,
+    pub line: u64,
+    pub text: String,
+}
+
+/// Searches text entries of the archive, every archive in the directory and the `other_archives`
+/// with an extended regex, descending into nested archives.
+pub fn search_path(
+    target: &FileMetadata,
+    pattern: &str,
+    other_archives: &[String],
+) -> Result<(), Box<dyn Error>> {
+    validate_pattern(pattern)?;
+
+    let mut archives = match target.is_directory {
+        true => find_archives_in_path(&target.path)?,
+        false => vec![target.path.to_owned()],
+    };
+    for archive in other_archives {
+        archives.push(file_metadata::get_file_metadata(archive)?.path);
+    }
+
+    let mut matches = Vec::new();
+    for archive in &archives {
+        search_archive(archive, archive, pattern, 0, &mut matches)?;
+    }
+    print_matches(&matches);
+    Ok(())
+}
+
+fn validate_pattern(pattern: &str) -> Result<(), Box<dyn Error>> {
+    // grep exits with 2 for invalid patterns and with 1 when nothing matched
+    let cmd = format!(
+        "printf '' | grep -E -- {}; [ $? -ne 2 ]",
+        shell_quote(pattern)
+    );
+    match execute_cmd(&cmd) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(OperationError::InvalidPattern(pattern.to_owned()).into()),
+    }
+}
+
+fn search_archive(
+    archive: &str,
+    location: &str,
+    pattern: &str,
+    depth: usize,
+    matches: &mut Vec<SearchMatch>,
+) -> Result<(), Box<dyn Error>> {
+    let entries = archive_entry::read_entries(archive)?;
+    let entries = path_safety::safe_entries(archive, &entries)?;
+    limits::check_entries(archive, &entries)?;
+
+    let (nested, texts): (Vec<&ArchiveEntry>, Vec<&ArchiveEntry>) = entries
+        .iter()
+        .filter(|entry| entry.kind == ArchiveEntryKind::File)
+        .partition(|entry| is_archive_path(entry.normalized_path()));
+
+    // every text entry is decompressed on the fly into grep, -I skips binary content
+    let texts = texts.into_iter().cloned().collect::<Vec<ArchiveEntry>>();
+    let grep = format!("grep -nIE -- {}", shell_quote(pattern));
+    let paths = texts
+        .iter()
+        .map(|entry| (entry.path.as_str(), entry.normalized_path()))
+        .collect::<HashMap<&str, &str>>();
+    for (stored_path, line) in cat::entries_output(archive, &texts, &grep)? {
+        let search_match = paths
+            .get(stored_path.as_str())
+            .and_then(|path| parse_grep_line(location, path, &line));
+        matches.extend(search_match);
+    }
+
+    // only nested archives are written to disk
+    for entry in nested {
+        let nested_location = format!(
+            "{location}{NESTED_ARCHIVE_SEPARATOR}{}",
+            entry.normalized_path()
+        );
+        let result = limits::check_depth(&nested_location, depth + 1).and_then(|_| {
+            search_nested(
+                archive,
+                entry,
+                &nested_location,
+                pattern,
+                depth + 1,
+                matches,
+            )
+        });
+        if let Err(error) = result {
+            output::status(&format!("Skipping '{nested_location}': {error}"));
+        }
+    }
+    Ok(())
+}
+
+/// Extracts only the nested archive and searches it.
+fn search_nested(
+    archive: &str,
+    entry: &ArchiveEntry,
+    location: &str,
+    pattern: &str,
+    depth: usize,
+    matches: &mut Vec<SearchMatch>,
+) -> Result<(), Box<dyn Error>> {
+    let dir = TempDir::new("nested")?;
+    unpacker::extract_members(archive, &dir.to_string_path(), std::slice::from_ref(entry))?;
+    let nested_archive = dir.path().join(&entry.path).display().to_string();
+    search_archive(&nested_archive, location, pattern, depth, matches)
+}
+
+/// `<line>:<text>` as printed by `grep -n`.
+fn parse_grep_line(location: &str, entry: &str, line: &str) -> Option<SearchMatch> {
+    let (line_number, text) = line.split_once(':')?;
+    Some(SearchMatch {
+        archive: location.to_owned(),
+        entry: entry.to_owned(),
+        line: line_number.parse().ok()?,
+        text: text.to_owned(),
+    })
+}
+
+fn print_matches(matches: &[SearchMatch]) {
+    if output::is_machine_readable() {
+        let items = matches
+            .iter()
+            .map(|search_match| {
+                Json::object(vec![
+                    ("archive", Json::from(search_match.archive.as_str())),
+                    ("entry", Json::from(search_match.entry.as_str())),
+                    ("line", Json::from(search_match.line)),
+                    ("text", Json::from(search_match.text.as_str())),
+                ])
+            })
+            .collect();
+        output::print_result("search", vec![], "matches", items);
+        return;
+    }
+    for search_match in matches {
+        println!(
+            "{}:{}:{}:{}",
+            search_match.archive, search_match.entry, search_match.line, search_match.text
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_grep_line() {
+        let search_match =
+            parse_grep_line("logs.tar.gz", "app/a:b.log", "12:ERROR: timeout").unwrap();
+        assert_eq!(
+            search_match,
+            SearchMatch {
+                archive: "logs.tar.gz".to_owned(),
+                entry: "app/a:b.log".to_owned(),
+                line: 12,
+                text: "ERROR: timeout".to_owned(),
+            }
+        );
+        assert!(parse_grep_line("logs.tar.gz", "app/a.bin", "Binary file matches").is_none());
+    }
+
+    #[test]
+    fn should_search_entries_and_nested_archives() {
+        let dir = TempDir::new("search_test").unwrap();
+        let root = shell_quote(&dir.to_string_path());
+        execute_cmd(&format!(
+            "cd {root} && mkdir -p in/logs && printf 'ok\\nERROR: disk\\n' > in/logs/app.log \\
+            && (cd in && zip -q ../inner.zip logs/app.log) && mv inner.zip in/ \\
+            && tar -czf outer.tar.gz -C in logs inner.zip"
+        ))
+        .unwrap();
+        let archive = dir.path().join("outer.tar.gz").display().to_string();
+
+        let mut matches = Vec::new();
+        search_archive(&archive, "outer.tar.gz", "ERROR", 0, &mut matches).unwrap();
+        let found = matches
+            .iter()
+            .map(|search_match| (search_match.archive.as_str(), search_match.entry.as_str()))
+            .collect::<Vec<(&str, &str)>>();
+        assert_eq!(
+            found,
+            vec![
+                ("outer.tar.gz", "logs/app.log"),
+                ("outer.tar.gz//inner.zip", "logs/app.log")
+            ]
+        );
+        assert_eq!(matches[0].line, 2);
+        assert_eq!(matches[0].text, "ERROR: disk");
+    }
+
+    #[test]
+    fn should_validate_pattern() {
+        assert!(validate_pattern("ERROR|WARN").is_ok());
+        assert!(validate_pattern("(unclosed").is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use crate::archive::archive_entry::{ArchiveEntry, ArchiveEntryKind};
+use crate::file::string_utils::glob_matches;
+
+/// Entries matching any of the paths or globs. Matching a directory selects everything below it,
+/// so `conf` selects `conf/app.yml`. Directory entries themselves are skipped - tar creates them on the way.
+pub fn select_entries(entries: &[ArchiveEntry], patterns: &[String]) -> Vec<ArchiveEntry> {
+    entries
+        .iter()
+        .filter(|entry| entry.kind != ArchiveEntryKind::Directory)
+        .filter(|entry| {
+            patterns
+                .iter()
+                .any(|pattern| matches_pattern(pattern, entry.normalized_path()))
+        })
+        .cloned()
+        .collect()
+}
+
+fn matches_pattern(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+    if glob_matches(pattern, path) {
+        return true;
+    }
+    path.match_indices('/')
+        .any(|(idx, _)| glob_matches(pattern, &path[..idx]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::archive_entry::test_entry;
+
+    #[test]
+    fn should_select_entries() {
+        let entries = vec![
+            test_entry("./backup/", ArchiveEntryKind::Directory),
+            test_entry("./backup/conf/", ArchiveEntryKind::Directory),
+            test_entry("./backup/conf/app.yml", ArchiveEntryKind::File),
+            test_entry("./backup/conf/db.yml", ArchiveEntryKind::File),
+            test_entry("./backup/data/blob.bin", ArchiveEntryKind::File),
+        ];
+        let select = |patterns: &[&str]| {
+            let patterns = patterns
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<String>>();
+            select_entries(&entries, &patterns)
+                .into_iter()
+                .map(|entry| entry.path)
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(
+            select(&["backup/conf/app.yml"]),
+            vec!["./backup/conf/app.yml"]
+        );
+        assert_eq!(
+            select(&["**/conf"]),
+            vec!["./backup/conf/app.yml", "./backup/conf/db.yml"]
+        );
+        assert_eq!(
+            select(&["*.bin", "app.yml"]),
+            vec!["./backup/conf/app.yml", "./backup/data/blob.bin"]
+        );
+        assert!(select(&["missing.txt"]).is_empty());
+    }
+}
@@ -0,0 +1,399 @@
+use crate::archive::archive_entry::{ArchiveEntry, ArchiveEntryKind};
+use crate::archive::list::format_ratio;
+use crate::archive::{archive_entry, cat};
+use crate::error::OperationError;
+use crate::file::file_metadata::FileMetadata;
+use crate::file::string_utils::find_file_extension;
+use crate::output;
+use crate::output::Json;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fs;
+
+pub const DEFAULT_TOP_ENTRIES: usize = 10;
+/// Entries saving less than this share of their size are reported as poorly compressed.
+const POOR_COMPRESSION_RATIO: f64 = 0.1;
+/// Smaller entries are not worth reporting as poorly compressed.
+const POOR_COMPRESSION_MIN_SIZE: u64 = 1024;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Group {
+    pub entries: usize,
+    pub size: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct ArchiveStats {
+    pub files: usize,
+    pub directories: usize,
+    pub links: usize,
+    pub uncompressed_size: u64,
+    pub compressed_size: u64,
+    pub by_extension: Vec<(String, Group)>,
+    pub by_directory: Vec<(String, Group)>,
+    pub largest: Vec<ArchiveEntry>,
+    /// `None` when the format does not store per-entry compressed sizes.
+    pub poorly_compressed: Option<Vec<ArchiveEntry>>,
+    pub duplicates: Vec<Vec<String>>,
+}
+
+pub fn stats_path(archive: &FileMetadata, top: usize) -> Result<(), Box<dyn Error>> {
+    if archive.is_directory {
+        return Err(OperationError::ArchiveIsDirectory.into());
+    }
+    let entries = archive_entry::read_entries(&archive.path)?;
+    let archive_size = fs::metadata(&archive.path)?.len();
+
+    let hashes = hash_duplicate_candidates(&archive.path, &entries)?;
+    let stats = compute(&entries, archive_size, &hashes, top);
+    match output::is_machine_readable() {
+        true => print_json_stats(&archive.path, &stats),
+        false => print_stats(&stats),
+    }
+    Ok(())
+}
+
+/// `hashes` are content hashes keyed by normalized entry path; `archive_size` is used as the compressed size
+/// of formats compressed as a whole, like `.tar.gz`.
+pub fn compute(
+    entries: &[ArchiveEntry],
+    archive_size: u64,
+    hashes: &BTreeMap<String, String>,
+    top: usize,
+) -> ArchiveStats {
+    let mut stats = ArchiveStats::default();
+    let mut by_extension: BTreeMap<String, Group> = BTreeMap::new();
+    let mut by_directory: BTreeMap<String, Group> = BTreeMap::new();
+    let has_entry_compression = entries.iter().all(|entry| entry.compressed_size.is_some());
+
+    for entry in entries {
+        match entry.kind {
+            ArchiveEntryKind::Directory => stats.directories += 1,
+            ArchiveEntryKind::Symlink | ArchiveEntryKind::Hardlink => stats.links += 1,
+            ArchiveEntryKind::File => {
+                stats.files += 1;
+                stats.uncompressed_size += entry.size;
+                add_to_group(&mut by_extension, extension_of(entry), entry.size);
+                add_to_group(&mut by_directory, directory_of(entry), entry.size);
+            }
+        }
+    }
+
+    stats.compressed_size = match has_entry_compression {
+        true => entries
+            .iter()
+            .filter_map(|entry| entry.compressed_size)
+            .sum(),
+        false => archive_size,
+    };
+    stats.by_extension = largest_groups(by_extension, top);
+    stats.by_directory = largest_groups(by_directory, top);
+
+    let mut files = entries
+        .iter()
+        .filter(|entry| entry.kind == ArchiveEntryKind::File)
+        .cloned()
+        .collect::<Vec<ArchiveEntry>>();
+    files.sort_by_key(|entry| Reverse(entry.size));
+
+    stats.poorly_compressed = match has_entry_compression {
+        true => Some(
+            files
+                .iter()
+                .filter(|entry| entry.size >= POOR_COMPRESSION_MIN_SIZE)
+                .filter(|entry| entry.ratio().unwrap_or(0.0) < POOR_COMPRESSION_RATIO)
+                .take(top)
+                .cloned()
+                .collect(),
+        ),
+        false => None,
+    };
+    stats.duplicates = find_duplicates(&files, hashes);
+    stats.largest = files.into_iter().take(top).collect();
+    stats
+}
+
+#[inline]
+fn add_to_group(groups: &mut BTreeMap<String, Group>, key: String, size: u64) {
+    let group = groups.entry(key).or_default();
+    group.entries += 1;
+    group.size += size;
+}
+
+fn largest_groups(groups: BTreeMap<String, Group>, top: usize) -> Vec<(String, Group)> {
+    let mut groups = groups.into_iter().collect::<Vec<(String, Group)>>();
+    groups.sort_by_key(|(_, group)| Reverse(group.size));
+    groups.truncate(top);
+    groups
+}
+
+fn extension_of(entry: &ArchiveEntry) -> String {
+    let path = entry.normalized_path();
+    let name = path.rsplit('/').next().unwrap_or(path);
+    match find_file_extension(name) {
+        Ok(ext) if ext.len() < name.len() => ext.to_lowercase(),
+        _ => "(none)".to_owned(),
+    }
+}
+
+fn directory_of(entry: &ArchiveEntry) -> String {
+    match entry.normalized_path().rsplit_once('/') {
+        Some((dir, _)) => dir.to_owned(),
+        None => ".".to_owned(),
+    }
+}
+
+/// Content hashes of the non-empty files sharing their size with another file, the only ones which may
+/// be duplicates, all hashed in a single pass over the archive.
+fn hash_duplicate_candidates(
+    archive: &str,
+    entries: &[ArchiveEntry],
+) -> Result<BTreeMap<String, String>, Box<dyn Error>> {
+    let files = entries
+        .iter()
+        .filter(|entry| entry.kind == ArchiveEntryKind::File && entry.size > 0)
+        .collect::<Vec<&ArchiveEntry>>();
+    let mut files_by_size: HashMap<u64, usize> = HashMap::new();
+    for entry in &files {
+        *files_by_size.entry(entry.size).or_default() += 1;
+    }
+    let candidates = files
+        .into_iter()
+        .filter(|entry| files_by_size[&entry.size] > 1)
+        .cloned()
+        .collect::<Vec<ArchiveEntry>>();
+    let paths = candidates
+        .iter()
+        .map(|entry| (entry.path.as_str(), entry.normalized_path()))
+        .collect::<HashMap<&str, &str>>();
+    Ok(cat::entries_output(archive, &candidates, "sha256sum")?
+        .into_iter()
+        .filter_map(|(stored_path, line)| {
+            let path = paths.get(stored_path.as_str())?;
+            let hash = line.split_whitespace().next()?;
+            Some((path.to_string(), hash.to_owned()))
+        })
+        .collect())
+}
+
+/// Groups of non-empty files with identical content, largest first.
+fn find_duplicates(files: &[ArchiveEntry], hashes: &BTreeMap<String, String>) -> Vec<Vec<String>> {
+    let mut groups: Vec<Vec<String>> = Vec::new();
+    let mut group_by_hash: HashMap<&str, usize> = HashMap::new();
+    // files are sorted by size, so the groups are created largest first
+    for entry in files.iter().filter(|entry| entry.size > 0) {
+        if let Some(hash) = hashes.get(entry.normalized_path()) {
+            let idx = *group_by_hash.entry(hash).or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[idx].push(entry.normalized_path().to_owned());
+        }
+    }
+    groups.retain(|paths| paths.len() > 1);
+    groups
+}
+
+fn print_stats(stats: &ArchiveStats) {
+    let ratio = match stats.uncompressed_size {
+        0 => None,
+        size => Some(1.0 - stats.compressed_size as f64 / size as f64),
+    };
+    println!(
+        "Entries: {} files, {} directories, {} links.",
+        stats.files, stats.directories, stats.links
+    );
+    println!(
+        "Size: {} bytes uncompressed, {} bytes compressed ({} saved).",
+        stats.uncompressed_size,
+        stats.compressed_size,
+        format_ratio(ratio)
+    );
+
+    println!("\nBy extension:");
+    for (extension, group) in &stats.by_extension {
+        println!(
+            "{:>12} bytes {:>6} entries  {extension}",
+            group.size, group.entries
+        );
+    }
+    println!("\nBy directory:");
+    for (dir, group) in &stats.by_directory {
+        println!(
+            "{:>12} bytes {:>6} entries  {dir}",
+            group.size, group.entries
+        );
+    }
+    println!("\nLargest entries:");
+    for entry in &stats.largest {
+        println!("{:>12} bytes  {}", entry.size, entry.path);
+    }
+
+    println!("\nPoorly compressed entries:");
+    match &stats.poorly_compressed {
+        None => println!("Not available - the format does not store compressed size per entry."),
+        Some(entries) => {
+            for entry in entries {
+                let ratio = format_ratio(entry.ratio());
+                println!("{:>12} bytes {ratio:>6} saved  {}", entry.size, entry.path);
+            }
+        }
+    }
+
+    println!("\nDuplicate content:");
+    for paths in &stats.duplicates {
+        println!("{}", paths.join(", "));
+    }
+}
+
+fn print_json_stats(archive: &str, stats: &ArchiveStats) {
+    let groups = |groups: &[(String, Group)]| {
+        Json::Array(
+            groups
+                .iter()
+                .map(|(name, group)| {
+                    Json::object(vec![
+                        ("name", Json::from(name.as_str())),
+                        ("entries", Json::from(group.entries as u64)),
+                        ("size", Json::from(group.size)),
+                    ])
+                })
+                .collect(),
+        )
+    };
+    let entries = |entries: &[ArchiveEntry]| {
+        Json::Array(entries.iter().map(|entry| entry.to_json()).collect())
+    };
+
+    let fields = vec![
+        ("archive", Json::from(archive)),
+        ("files", Json::from(stats.files as u64)),
+        ("directories", Json::from(stats.directories as u64)),
+        ("links", Json::from(stats.links as u64)),
+        ("uncompressed_size", Json::from(stats.uncompressed_size)),
+        ("compressed_size", Json::from(stats.compressed_size)),
+        ("by_extension", groups(&stats.by_extension)),
+        ("by_directory", groups(&stats.by_directory)),
+        ("largest", entries(&stats.largest)),
+        (
+            "poorly_compressed",
+            match &stats.poorly_compressed {
+                Some(poorly_compressed) => entries(poorly_compressed),
+                None => Json::Null,
+            },
+        ),
+        (
+            "duplicates",
+            Json::Array(
+                stats
+                    .duplicates
+                    .iter()
+                    .map(|paths| {
+                        Json::Array(paths.iter().map(|path| Json::from(path.as_str())).collect())
+                    })
+                    .collect(),
+            ),
+        ),
+    ];
+    output::print_result("stats", fields, "", vec![]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::archive_entry::test_entry;
+    use crate::cmd::command_runner::execute_cmd;
+    use crate::file::string_utils::shell_quote;
+    use crate::file::temp_dir::TempDir;
+
+    fn file(path: &str, size: u64, compressed_size: u64) -> ArchiveEntry {
+        ArchiveEntry {
+            size,
+            compressed_size: Some(compressed_size),
+            ..test_entry(path, ArchiveEntryKind::File)
+        }
+    }
+
+    #[test]
+    fn should_compute_stats() {
+        let entries = vec![
+            file("logs/a.log", 10_000, 1_000),
+            file("logs/b.log", 5_000, 500),
+            file("img/photo.JPG", 8_000, 7_900),
+            file("img/copy.jpg", 8_000, 7_900),
+            file("README", 100, 80),
+        ];
+        let hashes = BTreeMap::from([
+            ("img/photo.JPG".to_owned(), "same".to_owned()),
+            ("img/copy.jpg".to_owned(), "same".to_owned()),
+            ("logs/a.log".to_owned(), "a".to_owned()),
+        ]);
+
+        let stats = compute(&entries, 0, &hashes, 2);
+        assert_eq!(stats.files, 5);
+        assert_eq!(stats.uncompressed_size, 31_100);
+        assert_eq!(stats.compressed_size, 17_380);
+        assert_eq!(
+            stats.by_extension,
+            vec![
+                (
+                    ".jpg".to_owned(),
+                    Group {
+                        entries: 2,
+                        size: 16_000
+                    }
+                ),
+                (
+                    ".log".to_owned(),
+                    Group {
+                        entries: 2,
+                        size: 15_000
+                    }
+                ),
+            ]
+        );
+        assert_eq!(stats.by_directory[0].0, "img");
+        assert_eq!(stats.largest[0].path, "logs/a.log");
+
+        let poorly_compressed = stats.poorly_compressed.unwrap();
+        assert_eq!(poorly_compressed.len(), 2);
+        assert!(
+            poorly_compressed
+                .iter()
+                .all(|entry| entry.path.starts_with("img/"))
+        );
+        assert_eq!(
+            stats.duplicates,
+            vec![vec!["img/photo.JPG", "img/copy.jpg"]]
+        );
+    }
+
+    #[test]
+    fn should_hash_only_files_of_shared_size() {
+        let dir = TempDir::new("stats_test").unwrap();
+        execute_cmd(&format!(
+            "cd {} && printf same > a && printf same > b && printf diff > c && printf other > d \\
+            && zip -q files.zip a b c d && tar -czf files.tar.gz a b c d",
+            shell_quote(&dir.to_string_path())
+        ))
+        .unwrap();
+        for name in ["files.zip", "files.tar.gz"] {
+            let archive = dir.path().join(name).display().to_string();
+            let entries = archive_entry::read_entries(&archive).unwrap();
+            let hashes = hash_duplicate_candidates(&archive, &entries).unwrap();
+            assert_eq!(hashes.keys().collect::<Vec<&String>>(), vec!["a", "b", "c"]);
+            assert_eq!(hashes["a"], hashes["b"]);
+            assert_ne!(hashes["a"], hashes["c"]);
+        }
+    }
+
+    #[test]
+    fn should_use_archive_size_for_whole_archive_compression() {
+        let mut entry = file("a.txt", 100, 0);
+        entry.compressed_size = None;
+        let stats = compute(&[entry], 42, &BTreeMap::new(), DEFAULT_TOP_ENTRIES);
+        assert_eq!(stats.compressed_size, 42);
+        assert!(stats.poorly_compressed.is_none());
+    }
+}
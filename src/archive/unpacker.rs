@@ -0,0 +1,141 @@
+use crate::archive::archive_entry::ArchiveEntry;
+use crate::archive::{archive_entry, limits, path_safety};
+use crate::cmd::command_runner::execute_cmd;
+use crate::file::list_file::ListFile;
+use crate::file::string_utils::{find_file_extension, shell_quote};
+use crate::file::temp_dir::TempDir;
+use std::error::Error;
+
+/// Extracts the whole archive into a new temporary directory, preserving permissions.
+/// Entries which would be written outside the directory are left out, extraction limits are enforced.
+pub fn unpack_to_temp(archive: &str) -> Result<TempDir, Box<dyn Error>> {
+    let dir = TempDir::new("unpacked")?;
+    let entries = archive_entry::read_entries(archive)?;
+    let safe_entries = path_safety::safe_entries(archive, &entries)?;
+    limits::check_entries(archive, &safe_entries)?;
+    match safe_entries.len() == entries.len() {
+        true => execute_cmd(&limits::capped_cmd(
+            &entries,
+            &extract_cmd(archive, &shell_quote(&dir.to_string_path())),
+        ))?,
+        false => extract_members(archive, &dir.to_string_path(), &safe_entries)?,
+    }
+    Ok(dir)
+}
+
+/// `unzip` for ZIP archives (GNU tar can not read them), `tar` for everything else.
+/// `target_dir` is a shell word, e.g. `"$tmp"`.
+pub fn extract_cmd(archive: &str, target_dir: &str) -> String {
+    let is_zip = matches!(find_file_extension(archive), Ok(ext) if ext == ".zip");
+    let archive = shell_quote(archive);
+    match is_zip {
+        true => format!("unzip -qo {archive} -d {target_dir}"),
+        false => format!("tar -xpf {archive} -C {target_dir}"),
+    }
+}
+
+/// Names as the extracting tool matches them: `unzip` takes them as wildcards, so `[`, `*`, `?`
+/// and `\\` are escaped for ZIP archives.
+pub fn member_names(archive: &str, members: &[String]) -> Vec<String> {
+    let is_zip = matches!(find_file_extension(archive), Ok(ext) if ext == ".zip");
+    members
+        .iter()
+        .map(|member| match is_zip {
+            true => escape_wildcards(member),
+            false => member.to_owned(),
+        })
+        .collect()
+}
+
+#[inline]
+pub fn escape_wildcards(name: &str) -> String {
+    name.chars()
+        .flat_map(|c| match c {
+            '[' | '*' | '?' | '\\' => vec!['\\', c],
+            _ => vec![c],
+        })
+        .collect()
+}
+
+/// Extracts only the entries in the NUL separated `member_list`, named by `member_names`;
+/// directories without their content. `target_dir` is a shell word, e.g. `"$staging"`, and
+/// `tar_flags` are only used by `tar`.
+pub fn extract_members_cmd(
+    archive: &str,
+    target_dir: &str,
+    member_list: &str,
+    tar_flags: &str,
+) -> String {
+    let is_zip = matches!(find_file_extension(archive), Ok(ext) if ext == ".zip");
+    let archive = shell_quote(archive);
+    match is_zip {
+        true => format!("xargs -0 -r unzip -qo {archive} -d {target_dir} < {member_list}"),
+        false => format!(
+            "tar -xpf {archive} -C {target_dir}{tar_flags} --no-recursion --null -T {member_list}"
+        ),
+    }
+}
+
+/// Extracts only the given entries right away, none of the files growing past the largest of them.
+pub fn extract_members(
+    archive: &str,
+    target_dir: &str,
+    entries: &[ArchiveEntry],
+) -> Result<(), Box<dyn Error>> {
+    let members = entries
+        .iter()
+        .map(|entry| entry.path.to_owned())
+        .collect::<Vec<String>>();
+    let list = ListFile::new(&member_names(archive, &members))?;
+    execute_cmd(&limits::capped_cmd(
+        entries,
+        &extract_members_cmd(archive, &shell_quote(target_dir), &list.path(), ""),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn should_extract_members_with_wildcard_characters() {
+        assert_eq!(escape_wildcards("a[1]/b*?.txt"), "a\\[1]/b\\*\\?.txt");
+
+        let dir = TempDir::new("unpacker_test").unwrap();
+        let files = dir.path().join("files");
+        fs::create_dir_all(&files).unwrap();
+        for name in ["a[1].txt", "a1.txt", "-b.txt"] {
+            fs::write(files.join(name), name).unwrap();
+        }
+        let out = dir.path().join("out").display().to_string();
+        for archive in ["a.zip", "a.tar"] {
+            let archive = dir.path().join(archive).display().to_string();
+            let create = match archive.ends_with(".zip") {
+                true => "zip -q",
+                false => "tar -cf",
+            };
+            execute_cmd(&format!(
+                "cd {} && {create} {} ./*",
+                shell_quote(&files.display().to_string()),
+                shell_quote(&archive)
+            ))
+            .unwrap();
+            let members = archive_entry::read_entries(&archive)
+                .unwrap()
+                .into_iter()
+                .filter(|entry| entry.path.ends_with("a[1].txt") || entry.path.ends_with("-b.txt"))
+                .collect::<Vec<ArchiveEntry>>();
+            assert_eq!(members.len(), 2);
+            fs::create_dir_all(&out).unwrap();
+            extract_members(&archive, &out, &members).unwrap();
+            let mut extracted = fs::read_dir(&out)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+                .collect::<Vec<String>>();
+            extracted.sort();
+            assert_eq!(extracted, vec!["-b.txt", "a[1].txt"], "{archive}");
+            fs::remove_dir_all(&out).unwrap();
+        }
+    }
+}
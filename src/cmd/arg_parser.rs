@@ -1,43 +1,292 @@
-use crate::cmd::command_builder::{tar_path, unpack_all_in_path, unpack_path, zip_path};
-use crate::cmd::command_runner;
+use crate::archive::limits::ExtractionLimits;
+use crate::archive::list::SortKey;
+use crate::archive::{cat, diff, integrity, limits, list, nested, search, stats};
+use crate::cmd::command_builder::{
+    CreateOptions, ExtractOptions, tar_path, unpack_all_in_path, unpack_path, zip_path,
+};
+use crate::cmd::{
+    command_runner, conflicts, links, manifest, preserve, rewrite, signature, stored_root,
+};
 use crate::error::OperationError;
 use crate::file::file_metadata;
 use crate::file::file_metadata::FileMetadata;
+use crate::output;
+use crate::output::{Json, OutputFormat};
+use crate::tui::browser;
 use std::error::Error;
+use std::slice::Iter;
 
-const EXPECTED_NUMER_OF_ARGS: usize = 3;
+const MIN_NUMER_OF_ARGS: usize = 3;
+
+/// Options passed after the action and the path, e.g. `-z ./my/dir --manifest`.
+/// Arguments not starting with `-` are collected as `operands`, e.g. the directory in `--diff archive.zip ./dir`.
+#[derive(Debug, Default)]
+pub struct CliOptions {
+    pub operands: Vec<String>,
+    pub create: CreateOptions,
+    pub extract: ExtractOptions,
+    pub manifest_file: Option<String>,
+    pub porcelain: bool,
+    pub text_diff: bool,
+    pub filters: Vec<String>,
+    pub sort: Option<SortKey>,
+    pub format: OutputFormat,
+    pub top: Option<usize>,
+    pub limits: ExtractionLimits,
+}
 
 pub fn parse_and_run(cmd_args: Vec<String>) -> Result<(), Box<dyn Error>> {
     validate_number_of_args(cmd_args.len())?;
 
     let action = &cmd_args[1];
     let path = &cmd_args[2];
+    let mut options = parse_options(&cmd_args[MIN_NUMER_OF_ARGS..])?;
+    validate_options(action, &options)?;
+    output::set_format(options.format);
+    limits::set_limits(options.limits);
+    if let "-c" | "--cat" = action.as_str() {
+        output::redirect_status_to_stderr();
+    }
 
-    let metadata = file_metadata::get_file_metadata(path)?;
-
-    let cmd: String = parse_cmd(action, &metadata)?;
+    // keeps archives extracted from a nested path until the command ran
+    let resolved;
+    let cmd: String = match action.as_str() {
+        "-kg" | "--generate-keys" => signature::generate_keys_cmd(path)?,
+        "-a" | "--add" => return nested::add_to_path(path, &options.operands),
+        _ => {
+            resolved = nested::resolve(path)?;
+            if let Some(entry) = &resolved.entry {
+                apply_nested_entry(action, &mut options, entry)?;
+            }
+            let metadata = file_metadata::get_file_metadata(&resolved.archive)?;
+            if let Some(result) = run_operation(action, &metadata, &options) {
+                return result;
+            }
+            parse_cmd(action, &metadata, &options)?
+        }
+    };
     command_runner::run_command(&cmd)?;
+
+    if output::is_machine_readable() {
+        let fields = vec![
+            ("path", Json::from(path.as_str())),
+            ("command", Json::from(cmd)),
+        ];
+        output::print_result(operation_name(action), fields, "", vec![]);
+    }
+    Ok(())
+}
+
+fn operation_name(action: &str) -> &'static str {
+    match action {
+        "-x" | "-u" | "--extract" | "-d" | "--decompress" => "extract",
+        "-xa" | "-ax" | "--extract-all" => "extract_all",
+        "-z" | "--zip" | "-ze" | "-ez" | "--zip-encrypt" => "create_zip",
+        "-t" | "--tar" => "create_tar",
+        "-kg" | "--generate-keys" => "generate_keys",
+        "-vs" | "--verify-signature" => "verify_signature",
+        "-vm" | "--verify-manifest" => "verify_manifest",
+        _ => "unknown",
+    }
+}
+
+/// Rejects options the action would silently ignore.
+fn validate_options(action: &str, options: &CliOptions) -> Result<(), Box<dyn Error>> {
+    let verifies_signature = matches!(
+        operation_name(action),
+        "extract" | "extract_all" | "verify_signature"
+    );
+    if options.extract.verify_key.is_some() && !verifies_signature {
+        return Err(OperationError::UnsupportedOption(
+            "--verify-key".to_owned(),
+            action.to_owned(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Entry at the end of a nested path, e.g. `conf/app.yml` in `bundle.tar.gz//release.zip//conf/app.yml`,
+/// selects what the operation works on.
+fn apply_nested_entry(
+    action: &str,
+    options: &mut CliOptions,
+    entry: &str,
+) -> Result<(), Box<dyn Error>> {
+    match action {
+        "-c" | "--cat" => options.operands.insert(0, entry.to_owned()),
+        "-l" | "--list" => options
+            .filters
+            .extend([entry.to_owned(), format!("{entry}/**")]),
+        "-x" | "-u" | "--extract" | "-d" | "--decompress" => {
+            options.extract.patterns.push(entry.to_owned())
+        }
+        _ => return Err(OperationError::NotAnArchive(entry.to_owned()).into()),
+    }
     Ok(())
 }
 
 #[inline(always)]
 fn validate_number_of_args(number_of_args: usize) -> Result<(), Box<dyn Error>> {
-    if number_of_args != EXPECTED_NUMER_OF_ARGS {
-        let expected = EXPECTED_NUMER_OF_ARGS - 1;
+    if number_of_args < MIN_NUMER_OF_ARGS {
+        let expected = MIN_NUMER_OF_ARGS - 1;
         let actual = number_of_args - 1;
-        let msg = format!("Invalid number of arguments. Expected {expected}, but was {actual}.",);
+        let msg = format!(
+            "Invalid number of arguments. Expected at least {expected}, but was {actual}.",
+        );
         return Err(OperationError::FailedToRunCommand(msg).into());
     }
     Ok(())
 }
 
-fn parse_cmd(action: &str, metadata: &FileMetadata) -> Result<String, Box<dyn Error>> {
+fn parse_options(args: &[String]) -> Result<CliOptions, Box<dyn Error>> {
+    let mut options = CliOptions::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-m" | "--manifest" => options.create.with_manifest = true,
+            "--manifest-file" => options.manifest_file = Some(next_value(arg, &mut args)?),
+            "-r" | "--reproducible" | "--deterministic" => options.create.deterministic = true,
+            "--preserve" => {
+                let attributes = preserve::parse_attributes(&next_value(arg, &mut args)?)?;
+                options.create.preserve = attributes.to_owned();
+                options.extract.preserve = attributes;
+            }
+            "--base-dir" => options.create.root.base_dir = Some(next_value(arg, &mut args)?),
+            "--prefix" => {
+                options.create.root.prefix =
+                    Some(stored_root::parse_prefix(&next_value(arg, &mut args)?)?)
+            }
+            "--sign" => options.create.sign_key = Some(next_value(arg, &mut args)?),
+            "--verify-key" => options.extract.verify_key = Some(next_value(arg, &mut args)?),
+            "--porcelain" => options.porcelain = true,
+            "--text-diff" => options.text_diff = true,
+            "--filter" => options.filters.push(next_value(arg, &mut args)?),
+            "--format" => options.format = output::parse_format(&next_value(arg, &mut args)?)?,
+            "--top" => options.top = Some(parse_number(arg, &next_value(arg, &mut args)?)?),
+            "--sort" => options.sort = Some(list::parse_sort_key(&next_value(arg, &mut args)?)?),
+            "--links" => {
+                let mode = links::parse_link_mode(&next_value(arg, &mut args)?)?;
+                options.create.links = Some(mode);
+                options.extract.links = Some(mode);
+            }
+            "-o" | "--output-dir" => options.extract.output_dir = Some(next_value(arg, &mut args)?),
+            "--flat" => options.extract.flat = true,
+            "--smart" => options.extract.smart = true,
+            "--atomic" => options.extract.atomic = true,
+            "--force" => {
+                options.create.force = true;
+                options.extract.force = true;
+            }
+            "--strip-components" => {
+                options.extract.rewrite.strip_components =
+                    parse_number(arg, &next_value(arg, &mut args)?)?
+            }
+            "--rewrite" => options
+                .extract
+                .rewrite
+                .rules
+                .push(rewrite::parse_rule(&next_value(arg, &mut args)?)?),
+            "--on-conflict" => {
+                options.extract.on_conflict =
+                    conflicts::parse_conflict_policy(&next_value(arg, &mut args)?)?
+            }
+            "--max-bytes" => {
+                options.limits.max_bytes = limits::parse_size(arg, &next_value(arg, &mut args)?)?
+            }
+            "--max-entries" => {
+                options.limits.max_entries = parse_number(arg, &next_value(arg, &mut args)?)?
+            }
+            "--max-ratio" => {
+                options.limits.max_ratio = parse_number(arg, &next_value(arg, &mut args)?)?
+            }
+            "--max-depth" => {
+                options.limits.max_depth = parse_number(arg, &next_value(arg, &mut args)?)?
+            }
+            _ if !arg.starts_with('-') => options.operands.push(arg.to_owned()),
+            _ => return Err(OperationError::InvalidArgument(arg.to_owned()).into()),
+        }
+    }
+    // operands of extraction select the entries to extract
+    options.extract.patterns = options.operands.clone();
+    Ok(options)
+}
+
+#[inline]
+fn next_value(option: &str, args: &mut Iter<String>) -> Result<String, Box<dyn Error>> {
+    match args.next() {
+        Some(value) => Ok(value.to_owned()),
+        None => Err(OperationError::MissingOptionValue(option.to_owned()).into()),
+    }
+}
+
+#[inline]
+fn operand(options: &CliOptions, idx: usize, name: &str) -> Result<String, Box<dyn Error>> {
+    match options.operands.get(idx) {
+        Some(value) => Ok(value.to_owned()),
+        None => Err(OperationError::MissingOperand(name.to_owned()).into()),
+    }
+}
+
+/// Operations executed directly by the program instead of a shell command.
+fn run_operation(
+    action: &str,
+    metadata: &FileMetadata,
+    options: &CliOptions,
+) -> Option<Result<(), Box<dyn Error>>> {
+    let result = match action {
+        "--test" => integrity::test_path(metadata),
+        "-s" | "--stats" => {
+            stats::stats_path(metadata, options.top.unwrap_or(stats::DEFAULT_TOP_ENTRIES))
+        }
+        "-g" | "--grep" => operand(options, 0, "pattern")
+            .and_then(|pattern| search::search_path(metadata, &pattern, &options.operands[1..])),
+        "-c" | "--cat" => {
+            operand(options, 0, "entry").and_then(|entry| cat::cat_path(metadata, &entry))
+        }
+        "-l" | "--list" => list::list_path(metadata, &options.filters, options.sort),
+        "-b" | "--browse" => browser::browse_path(metadata),
+        "-df" | "--diff" => operand(options, 0, "directory")
+            .and_then(|dir| file_metadata::get_file_metadata(&dir))
+            .and_then(|dir| diff::diff_archive_with_dir(metadata, &dir, options.porcelain)),
+        "-da" | "--diff-archives" => operand(options, 0, "second archive")
+            .and_then(|new| file_metadata::get_file_metadata(&new))
+            .and_then(|new| {
+                diff::diff_archives(metadata, &new, options.porcelain, options.text_diff)
+            }),
+        _ => return None,
+    };
+    Some(result)
+}
+
+#[inline]
+fn parse_number<T: std::str::FromStr>(option: &str, value: &str) -> Result<T, Box<dyn Error>> {
+    match value.parse::<T>() {
+        Ok(number) => Ok(number),
+        Err(_) => Err(OperationError::InvalidArgument(format!("{option} {value}")).into()),
+    }
+}
+
+fn parse_cmd(
+    action: &str,
+    metadata: &FileMetadata,
+    options: &CliOptions,
+) -> Result<String, Box<dyn Error>> {
     let cmd = match action {
-        "-x" | "-u" | "--extract" | "-d" | "--decompress" => unpack_path(&metadata.path)?,
-        "-xa" | "-ax" | "--extract-all" => unpack_all_in_path(&metadata.path)?,
-        "-z" | "--zip" => zip_path(metadata, false)?,
-        "-ze" | "-ez" | "--zip-encrypt" => zip_path(metadata, true)?,
-        "-t" | "--tar" => tar_path(metadata)?,
+        "-x" | "-u" | "--extract" | "-d" | "--decompress" => {
+            unpack_path(&metadata.path, &options.extract)?
+        }
+        "-xa" | "-ax" | "--extract-all" => unpack_all_in_path(&metadata.path, &options.extract)?,
+        "-z" | "--zip" => zip_path(metadata, false, &options.create)?,
+        "-ze" | "-ez" | "--zip-encrypt" => zip_path(metadata, true, &options.create)?,
+        "-t" | "--tar" => tar_path(metadata, &options.create)?,
+        "-vs" | "--verify-signature" => match &options.extract.verify_key {
+            Some(key) => signature::verify_cmd(&metadata.path, key)?,
+            None => return Err(OperationError::PublicKeyNotProvided.into()),
+        },
+        "-vm" | "--verify-manifest" => {
+            manifest::verify_manifest(metadata, options.manifest_file.as_deref())?
+        }
         _ => {
             return Err(
                 OperationError::InvalidArgument(format!("Invalid argument {action}.")).into(),
@@ -71,21 +320,140 @@ mod tests {
 
     #[test]
     fn parse_decompress() {
+        let archive = format!("{TEST_ARCHIVE_FILES}/resources_archive.tar");
+        let metadata = file_metadata::get_file_metadata(&archive).unwrap();
         for arg in ["-u", "--extract", "-d", "--decompress", "-x"] {
             let expected_path = env::current_dir().unwrap().display().to_string();
-            let cmd = parse_cmd(arg, &TEST_METADATA).unwrap();
+            let cmd = parse_cmd(arg, &metadata, &CliOptions::default()).unwrap();
 
+            // no file may grow past the largest listed entry
             assert_eq!(
                 cmd,
-                format!("tar -xvf '{TEST_FILES}' -C '{expected_path}/extracted_files'")
+                format!(
+                    "(ulimit -f 1 && tar -xvf '{archive}' -C '{expected_path}/extracted_files')"
+                )
             );
         }
     }
 
+    #[test]
+    fn parse_decompress_selected_entries() {
+        let expected_path = env::current_dir().unwrap().display().to_string();
+        let archive = format!("{TEST_ARCHIVE_FILES}/resources_archive.tar");
+        let metadata = file_metadata::get_file_metadata(&archive).unwrap();
+        let options = parse_options(&["**/test1.*".to_owned(), "test4.png".to_owned()]).unwrap();
+        let cmd = parse_cmd("-x", &metadata, &options).unwrap();
+
+        // members are listed in a file, removed by the command
+        let extraction =
+            format!("&& tar -xvf '{archive}' -C '{expected_path}/extracted_files' --null -T '");
+        assert!(cmd.ends_with("'))"));
+        let list = &cmd[cmd.find(&extraction).unwrap() + extraction.len()..cmd.len() - 3];
+        assert!(cmd.starts_with(&format!("(trap 'rm -f '\\''{list}'\\''' EXIT")));
+        assert_eq!(
+            std::fs::read_to_string(list).unwrap(),
+            "./resources/test/test1.txt\0./resources/test/test4.png\0"
+        );
+        std::fs::remove_file(list).unwrap();
+    }
+
+    #[test]
+    fn parse_decompress_to_output_dir() {
+        let expected_path = env::current_dir().unwrap().display().to_string();
+        let archive = format!("{TEST_ARCHIVE_FILES}/resources_archive.tar");
+        let metadata = file_metadata::get_file_metadata(&archive).unwrap();
+        let output_dir = format!("target/output_dir_test_{}", std::process::id());
+        let options = parse_options(&["-o".to_owned(), output_dir.to_owned()]).unwrap();
+        let cmd = parse_cmd("-x", &metadata, &options).unwrap();
+
+        assert_eq!(
+            cmd,
+            format!("(ulimit -f 1 && tar -xvf '{archive}' -C '{expected_path}/{output_dir}')")
+        );
+        assert!(std::path::Path::new(&output_dir).is_dir());
+        std::fs::remove_dir(&output_dir).unwrap();
+    }
+
+    #[test]
+    fn parse_decompress_smart() {
+        let expected_path = env::current_dir().unwrap().display().to_string();
+        let archive = format!("{TEST_ARCHIVE_FILES}/resources_archive.tar");
+        let metadata = file_metadata::get_file_metadata(&archive).unwrap();
+        let options = parse_options(&["--smart".to_owned()]).unwrap();
+        let cmd = parse_cmd("-x", &metadata, &options).unwrap();
+
+        // everything is in the `resources` directory already
+        assert_eq!(
+            cmd,
+            format!("(ulimit -f 1 && tar -xvf '{archive}' -C '{expected_path}/extracted_files')")
+        );
+    }
+
+    #[test]
+    fn extract_zip_selected_entries() {
+        let archive = format!("{TEST_ARCHIVE_FILES}/resources_archive.zip");
+        let metadata = file_metadata::get_file_metadata(&archive).unwrap();
+        let dir = crate::file::temp_dir::TempDir::new("zip_selection_test").unwrap();
+        let options = parse_options(&[
+            "**/test1.*".to_owned(),
+            "test4.png".to_owned(),
+            "-o".to_owned(),
+            dir.to_string_path(),
+        ])
+        .unwrap();
+        let cmd = parse_cmd("-x", &metadata, &options).unwrap();
+        assert!(!cmd.contains("tar -x"));
+        crate::cmd::command_runner::execute_cmd(&cmd).unwrap();
+
+        let mut extracted = std::fs::read_dir(dir.path().join("resources/test"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect::<Vec<String>>();
+        extracted.sort();
+        assert_eq!(extracted, vec!["test1.txt", "test4.png"]);
+    }
+
+    #[test]
+    fn extract_zip_with_stripped_components() {
+        let archive = format!("{TEST_ARCHIVE_FILES}/resources_archive.zip");
+        let metadata = file_metadata::get_file_metadata(&archive).unwrap();
+        let dir = crate::file::temp_dir::TempDir::new("strip_test").unwrap();
+        let output_dir = dir.to_string_path();
+        let options = parse_options(&[
+            "--strip-components".to_owned(),
+            "1".to_owned(),
+            "-o".to_owned(),
+            output_dir.to_owned(),
+        ])
+        .unwrap();
+        let cmd = parse_cmd("-x", &metadata, &options).unwrap();
+        crate::cmd::command_runner::execute_cmd(&cmd).unwrap();
+
+        let mut extracted = std::fs::read_dir(dir.path().join("test"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect::<Vec<String>>();
+        extracted.sort();
+        assert_eq!(
+            extracted,
+            vec!["test1.txt", "test2.txt", "test3.txt", "test4.png"]
+        );
+        // the staging directory is removed
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn parse_decompress_not_matching_entries() {
+        let archive = format!("{TEST_ARCHIVE_FILES}/resources_archive.tar");
+        let metadata = file_metadata::get_file_metadata(&archive).unwrap();
+        let options = parse_options(&["missing.txt".to_owned()]).unwrap();
+        assert!(parse_cmd("-x", &metadata, &options).is_err());
+    }
+
     #[test]
     fn parse_zip() {
         for arg in ["-z", "--zip"] {
-            let cmd = parse_cmd(arg, &TEST_METADATA).unwrap();
+            let cmd = parse_cmd(arg, &TEST_METADATA, &CliOptions::default()).unwrap();
             assert_eq!("zip -r files_archive.zip ./resources/test/files/*", cmd);
         }
     }
@@ -93,7 +461,7 @@ mod tests {
     #[test]
     fn parse_zip_encrypt() {
         for arg in ["-ze", "-ez", "--zip-encrypt"] {
-            let cmd = parse_cmd(arg, &TEST_METADATA).unwrap();
+            let cmd = parse_cmd(arg, &TEST_METADATA, &CliOptions::default()).unwrap();
             assert_eq!("zip -re files_archive.zip ./resources/test/files/*", cmd);
         }
     }
@@ -101,18 +469,153 @@ mod tests {
     #[test]
     fn parse_tar() {
         for arg in ["-t", "--tar"] {
-            let cmd = parse_cmd(arg, &TEST_METADATA).unwrap();
+            let cmd = parse_cmd(arg, &TEST_METADATA, &CliOptions::default()).unwrap();
             assert_eq!("tar -cf files_archive.tar ./resources/test/files/*", cmd);
         }
     }
 
+    #[test]
+    fn parse_zip_with_manifest() {
+        let options = parse_options(&["--manifest".to_owned()]).unwrap();
+        let cmd = parse_cmd("-z", &TEST_METADATA, &options).unwrap();
+        assert_eq!(
+            "zip -r files_archive.zip ./resources/test/files/* && \
+            find ./resources/test/files/* -type f -exec sha256sum {} + | sed 's#  /#  #' \
+            | LC_ALL=C sort -k2 > 'files_archive.zip.sha256'",
+            cmd
+        );
+    }
+
+    #[test]
+    fn parse_deterministic_zip_encrypt() {
+        let options = parse_options(&["--deterministic".to_owned()]).unwrap();
+        assert!(parse_cmd("-ze", &TEST_METADATA, &options).is_err());
+    }
+
+    #[test]
+    fn parse_verify_manifest_of_dir_requires_manifest_file() {
+        for arg in ["-vm", "--verify-manifest"] {
+            let cmd = parse_cmd(arg, &TEST_METADATA, &CliOptions::default());
+            assert!(cmd.is_err());
+        }
+    }
+
+    #[test]
+    fn parse_extract_refuses_unsigned_archive() {
+        let options = parse_options(&[
+            "--verify-key".to_owned(),
+            "./resources/test/files/test1.txt".to_owned(),
+        ])
+        .unwrap();
+        let metadata = FileMetadata {
+            path: format!("{TEST_ARCHIVE_FILES}/resources_archive.tar"),
+            wildcard: None,
+            is_directory: false,
+        };
+        for arg in ["-x", "-vs"] {
+            let error = parse_cmd(arg, &metadata, &options).unwrap_err();
+            assert!(error.to_string().contains("is not signed"));
+        }
+        let error = parse_cmd("-xa", &TEST_ARCHIVES_METADATA, &options).unwrap_err();
+        assert!(error.to_string().contains("is not signed"));
+    }
+
+    #[test]
+    fn verify_key_only_with_verifying_actions() {
+        let options =
+            parse_options(&["--verify-key".to_owned(), "./key.pub.pem".to_owned()]).unwrap();
+        for action in ["-x", "-xa", "-vs"] {
+            assert!(validate_options(action, &options).is_ok());
+        }
+        for action in ["-z", "-t", "-l", "--cat"] {
+            assert!(validate_options(action, &options).is_err());
+        }
+    }
+
+    #[test]
+    fn parse_operands() {
+        let options = parse_options(&["./my/dir".to_owned(), "--porcelain".to_owned()]).unwrap();
+        assert_eq!(options.operands, vec!["./my/dir"]);
+        assert!(options.porcelain);
+    }
+
+    #[test]
+    fn parse_list_options() {
+        let args = ["--sort", "size", "--filter", "*.txt", "--filter", "*.png"].map(String::from);
+        let options = parse_options(&args).unwrap();
+        assert_eq!(options.sort, Some(SortKey::Size));
+        assert_eq!(options.filters, vec!["*.txt", "*.png"]);
+    }
+
+    #[test]
+    fn parse_top() {
+        let args = ["--top", "5"].map(String::from);
+        assert_eq!(parse_options(&args).unwrap().top, Some(5));
+
+        let args = ["--top", "five"].map(String::from);
+        assert!(parse_options(&args).is_err());
+    }
+
+    #[test]
+    fn parse_output_format() {
+        let args = ["--format", "ndjson"].map(String::from);
+        assert_eq!(parse_options(&args).unwrap().format, OutputFormat::Ndjson);
+
+        let args = ["--format", "xml"].map(String::from);
+        assert!(parse_options(&args).is_err());
+    }
+
+    #[test]
+    fn test_archive_integrity() {
+        let metadata = FileMetadata {
+            path: format!("{TEST_ARCHIVE_FILES}/resources_archive.zip"),
+            wildcard: None,
+            is_directory: false,
+        };
+        let result = run_operation("--test", &metadata, &CliOptions::default()).unwrap();
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn cat_directory_entry() {
+        let metadata = FileMetadata {
+            path: format!("{TEST_ARCHIVE_FILES}/resources_archive.tar"),
+            wildcard: None,
+            is_directory: false,
+        };
+        let options = parse_options(&["./resources/test/".to_owned()]).unwrap();
+        let error = run_operation("--cat", &metadata, &options)
+            .unwrap()
+            .unwrap_err();
+        assert!(error.to_string().contains("is a directory"));
+    }
+
+    #[test]
+    fn diff_requires_directory() {
+        let metadata = FileMetadata {
+            path: format!("{TEST_ARCHIVE_FILES}/resources_archive.tar"),
+            wildcard: None,
+            is_directory: false,
+        };
+        let result = run_operation("--diff", &metadata, &CliOptions::default()).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_unknown_option() {
+        assert!(parse_options(&["--unknown".to_owned()]).is_err());
+        assert!(parse_options(&["--manifest-file".to_owned()]).is_err());
+    }
+
     #[test]
     fn extract_multiple_archives() {
         for arg in ["-xa", "-ax", "--extract-all"] {
-            let cmd = parse_cmd(arg, &TEST_ARCHIVES_METADATA).unwrap();
+            let cmd = parse_cmd(arg, &TEST_ARCHIVES_METADATA, &CliOptions::default()).unwrap();
             assert!(cmd.contains("tar -xvf './resources/test/archives/resources_archive.tar'"));
-            assert!(cmd.contains("tar -xvf './resources/test/archives/resources_archive.zip'"));
+            assert!(cmd.contains("unzip -qo './resources/test/archives/resources_archive.zip'"));
             assert!(cmd.contains(" && "));
+            assert!(cmd.contains("/extracted_files/resources_archive'"));
+            assert!(cmd.contains("/extracted_files/resources_archive_1'"));
 
             // may be collected in different order
             // assert_eq!(
@@ -122,4 +625,18 @@ mod tests {
             // );
         }
     }
+
+    #[test]
+    fn extract_multiple_archives_smart() {
+        let options = parse_options(&["--smart".to_owned()]).unwrap();
+        let cmd = parse_cmd("-xa", &TEST_ARCHIVES_METADATA, &options).unwrap();
+        assert!(cmd.contains("/extracted_files/resources_archive'"));
+        assert!(cmd.contains("/extracted_files/resources_archive_1'"));
+
+        // both archives have a single top-level directory
+        let options = parse_options(&["--smart".to_owned(), "--flat".to_owned()]).unwrap();
+        let cmd = parse_cmd("-xa", &TEST_ARCHIVES_METADATA, &options).unwrap();
+        assert!(cmd.contains("/extracted_files')"));
+        assert!(!cmd.contains("/extracted_files/"));
+    }
 }
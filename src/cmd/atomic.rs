@@ -0,0 +1,71 @@
+use crate::file::string_utils::shell_quote;
+use std::path::Path;
+
+/// Shell word of the staging directory the steps wrapped by `wrap_cmd` extract to.
+pub const STAGING_DIR: &str = "\"$atomic_staging\"";
+
+/// Runs the extraction steps into a hidden staging directory next to `target_dir`, created when the
+/// command runs, e.g. `./.extracted_files.atomic.x3Fq9Z` for `./extracted_files`, so it is on the same
+/// filesystem and renaming out of it does not copy. The result is moved into `target_dir` only when
+/// every step succeeded: an empty target is replaced by the staging directory in a single rename,
+/// otherwise every file is renamed into place, one at a time. The staging directory is removed on
+/// failure and on Ctrl-C; the subshell keeps the traps away from the commands chained around it.
+pub fn wrap_cmd(steps: &str, target_dir: &str) -> String {
+    let target = Path::new(target_dir);
+    let name = target
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let template = target
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join(format!(".{name}.atomic.XXXXXX"))
+        .display()
+        .to_string();
+    let target = shell_quote(target_dir);
+    format!(
+        "(atomic_staging= && trap 'rm -rf {STAGING_DIR}' EXIT && trap 'exit 130' INT TERM \
+        && atomic_staging=$(mktemp -d {}) && {steps} \
+        && if rmdir {target} 2>/dev/null; then mv {STAGING_DIR} {target}; \
+        else cd {STAGING_DIR} && find . -mindepth 1 -type d -exec mkdir -p {target}/{{}} \\; \
+        && find . -mindepth 1 ! -type d -exec mv -f {{}} {target}/{{}} \\; ; fi)",
+        shell_quote(&template)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::command_runner::execute_cmd;
+    use crate::file::temp_dir::TempDir;
+    use std::fs;
+
+    #[test]
+    fn should_move_only_complete_extraction() {
+        let dir = TempDir::new("atomic_test").unwrap();
+        let target_dir = dir.path().join("out").display().to_string();
+        fs::create_dir_all(dir.path().join("out/kept")).unwrap();
+        fs::write(dir.path().join("out/kept/old.txt"), "old").unwrap();
+        let entries = || fs::read_dir(dir.path()).unwrap().count();
+
+        let failing = format!(
+            "mkdir -p {STAGING_DIR}/kept && echo new > {STAGING_DIR}/kept/new.txt && false"
+        );
+        assert!(execute_cmd(&wrap_cmd(&failing, &target_dir)).is_err());
+        assert_eq!(entries(), 1);
+        assert!(!dir.path().join("out/kept/new.txt").exists());
+
+        let succeeding =
+            format!("mkdir -p {STAGING_DIR}/kept && echo new > {STAGING_DIR}/kept/new.txt");
+        execute_cmd(&wrap_cmd(&succeeding, &target_dir)).unwrap();
+        assert!(dir.path().join("out/kept/new.txt").exists());
+        assert!(dir.path().join("out/kept/old.txt").exists());
+        assert_eq!(entries(), 1);
+
+        let empty_target = dir.path().join("empty").display().to_string();
+        fs::create_dir_all(&empty_target).unwrap();
+        execute_cmd(&wrap_cmd(&succeeding, &empty_target)).unwrap();
+        assert!(dir.path().join("empty/kept/new.txt").exists());
+        assert_eq!(entries(), 2);
+    }
+}
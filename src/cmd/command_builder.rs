@@ -1,11 +1,23 @@
-use crate::cmd::command_runner::{execute_cmd_get_lines, run_command};
+use crate::archive::archive_entry::{ArchiveEntry, ArchiveEntryKind};
+use crate::archive::{archive_entry, limits, path_safety, selection, unpacker};
+use crate::cmd::command_runner::execute_cmd_get_lines;
+use crate::cmd::conflicts::{ConflictPlan, ConflictPolicy};
+use crate::cmd::links::LinkMode;
+use crate::cmd::preserve::Attribute;
+use crate::cmd::rewrite::PathRewrite;
+use crate::cmd::stored_root::{StoredFiles, StoredRoot};
+use crate::cmd::{atomic, conflicts, links, manifest, preserve, reproducible, rewrite, signature};
+use crate::error::OperationError;
 use crate::file::file_metadata::FileMetadata;
-use crate::file::{file_metadata, string_utils};
+use crate::file::string_utils::shell_quote;
+use crate::file::{disk_space, file_metadata, list_file, string_utils};
 use crate::input_handler;
+use crate::output;
 use lazy_static::lazy_static;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::error::Error;
+use std::fs;
 
 lazy_static! {
     static ref VALID_ARCHIVE_FORMATS: HashSet<&'static str> = HashSet::from_iter(vec![
@@ -20,78 +32,607 @@ lazy_static! {
         .join("|");
 }
 
+/// Additional steps applied when a new archive is created.
+#[derive(Debug, Default)]
+pub struct CreateOptions {
+    pub with_manifest: bool,
+    pub sign_key: Option<String>,
+    pub deterministic: bool,
+    /// `None` keeps the default of the format: tar preserves links, zip follows them.
+    pub links: Option<LinkMode>,
+    pub root: StoredRoot,
+    pub preserve: Vec<Attribute>,
+    /// Creates the archive even when the working directory does not have enough free space.
+    pub force: bool,
+}
+
+/// How archives are extracted.
+#[derive(Debug, Clone, Default)]
+pub struct ExtractOptions {
+    /// Entry paths or globs to extract; everything is extracted when empty.
+    pub patterns: Vec<String>,
+    /// `None` extracts links as the archive stores them.
+    pub links: Option<LinkMode>,
+    pub on_conflict: ConflictPolicy,
+    /// Relative paths are resolved against the working directory; `./extracted_files` when `None`.
+    pub output_dir: Option<String>,
+    /// Extracts every archive of a directory straight into the output directory instead of
+    /// a subdirectory named after the archive.
+    pub flat: bool,
+    /// Wraps the content in a directory named after the archive, unless it already has
+    /// a single top-level directory.
+    pub smart: bool,
+    pub rewrite: PathRewrite,
+    pub preserve: Vec<Attribute>,
+    /// Extracts to a hidden staging directory first and moves the result into place only on success.
+    pub atomic: bool,
+    /// Extracts even when the destination does not have enough free space.
+    pub force: bool,
+    /// Public key the detached signature of every extracted archive has to match.
+    pub verify_key: Option<String>,
+}
+
 #[inline]
 pub fn unpack() -> Result<String, Box<dyn Error>> {
     let file = input_handler::read_path_to_archive()?;
-    unpack_path(&file)
+    let patterns = input_handler::read_optional(
+        "Entries to extract - paths or globs separated by spaces (leave empty for all):",
+    );
+    let options = ExtractOptions {
+        patterns: patterns
+            .map(|patterns| patterns.split_whitespace().map(String::from).collect())
+            .unwrap_or_default(),
+        output_dir: read_output_dir(),
+        ..ExtractOptions::default()
+    };
+    unpack_path(&file, &options)
+}
+
+pub fn unpack_path(path: &str, options: &ExtractOptions) -> Result<String, Box<dyn Error>> {
+    list_file::finish(
+        unpack_cmd(path, options).and_then(|(cmd, target_dir, required_bytes)| {
+            let output_dir = resolve_output_dir(options.output_dir.as_deref())?;
+            disk_space::check(&output_dir, required_bytes, options.force)?;
+            fs::create_dir_all(&target_dir)?;
+            Ok(cmd)
+        }),
+    )
+}
+
+/// Extraction command with the directory it extracts to and the number of bytes it writes.
+/// Nothing is written yet, the directory is created by the caller once the space was checked.
+fn unpack_cmd(
+    path: &str,
+    options: &ExtractOptions,
+) -> Result<(String, String, u64), Box<dyn Error>> {
+    let verification = match &options.verify_key {
+        Some(key) => Some(signature::verify_cmd(path, key)?),
+        None => None,
+    };
+    let entries = archive_entry::read_entries(path)?;
+    let mut target_dir = resolve_output_dir(options.output_dir.as_deref())?;
+    if options.smart && top_level_directory(&entries).is_none() {
+        target_dir = format!("{target_dir}/{}", archive_stem(path));
+        output::status(&format!(
+            "No single top-level directory in '{path}', extracting to '{target_dir}'."
+        ));
+    }
+    let rewritten_paths = match options.rewrite.is_empty() {
+        true => None,
+        false => Some(options.rewrite.rewrite_paths(&entries)?),
+    };
+    let (members, conflict_plan) = select_members(
+        path,
+        &entries,
+        options,
+        &target_dir,
+        rewritten_paths.as_ref(),
+    )?;
+
+    let extracted = conflict_plan
+        .extract
+        .iter()
+        .chain(conflict_plan.renamed.iter().map(|(entry, _)| entry))
+        .cloned()
+        .collect::<Vec<ArchiveEntry>>();
+    let required_bytes = extracted.iter().map(|entry| entry.size).sum::<u64>();
+
+    let is_zip = matches!(string_utils::find_file_extension(path), Ok(ext) if ext == ".zip");
+    preserve::report_unsupported(
+        path,
+        &preserve::unsupported_on_extraction(is_zip, &options.preserve),
+    );
+    let preserve_flags = preserve::extract_flags(is_zip, &options.preserve);
+
+    // shell word, the atomic staging directory is only created when the command runs
+    let extract_dir = match options.atomic {
+        true => atomic::STAGING_DIR.to_owned(),
+        false => string_utils::shell_quote(&target_dir),
+    };
+    // renamed entries keep their new names, relative to the directory they are extracted to
+    let target_prefix = format!("{target_dir}/");
+    let renamed = conflict_plan
+        .renamed
+        .iter()
+        .map(|(entry, destination)| {
+            let destination = destination
+                .strip_prefix(&target_prefix)
+                .unwrap_or(destination);
+            (entry.clone(), destination.to_owned())
+        })
+        .collect::<Vec<(ArchiveEntry, String)>>();
+
+    let mut steps = Vec::new();
+    let extracted_entries = match rewritten_paths {
+        None => {
+            steps.push(extract_step(
+                path,
+                is_zip,
+                &extract_dir,
+                &preserve_flags,
+                members,
+            ));
+            steps.extend(conflicts::extract_renamed_cmd(
+                path,
+                &preserve_flags,
+                &renamed,
+                &extract_dir,
+            ));
+            conflict_plan.extract
+        }
+        Some(rewritten_paths) => {
+            // entries as they are stored after extraction
+            let rewritten_entries = conflict_plan
+                .extract
+                .iter()
+                .map(|entry| ArchiveEntry {
+                    path: rewritten_paths[&entry.path].to_owned(),
+                    ..entry.clone()
+                })
+                .collect::<Vec<ArchiveEntry>>();
+            let mut moves = conflict_plan
+                .extract
+                .into_iter()
+                .zip(&rewritten_entries)
+                .map(|(entry, rewritten)| (entry, rewritten.path.to_owned()))
+                .collect::<Vec<(ArchiveEntry, String)>>();
+            moves.extend(renamed);
+            steps.push(rewrite::extract_cmd(
+                path,
+                &preserve_flags,
+                &moves,
+                &extract_dir,
+            ));
+            rewritten_entries
+        }
+    };
+    // the listing was checked against the limits, the data may still expand further
+    let mut steps = vec![limits::capped_cmd(&extracted, &steps.join(" && "))];
+    steps.extend(links::materialize_links_cmd(
+        options.links,
+        &extracted_entries,
+        &extract_dir,
+    ));
+
+    let cmd = match options.atomic {
+        true => atomic::wrap_cmd(&steps.join(" && "), &target_dir),
+        false => steps.join(" && "),
+    };
+    let cmd = match verification {
+        Some(verification) => format!("{verification} && {cmd}"),
+        None => cmd,
+    };
+    Ok((cmd, target_dir, required_bytes))
+}
+
+/// Extracts the selected members of the archive, or all of them, to `extract_dir` (a shell word).
+/// ZIP archives go through `unzip`, which never recurses into listed directories.
+fn extract_step(
+    path: &str,
+    is_zip: bool,
+    extract_dir: &str,
+    preserve_flags: &str,
+    members: Option<MemberList>,
+) -> String {
+    match (is_zip, members) {
+        (true, None) => unpacker::extract_cmd(path, extract_dir),
+        (true, Some(members)) => {
+            unpacker::extract_members_cmd(path, extract_dir, &members.path, "")
+        }
+        (false, None) => format!("tar -xvf '{path}' -C {extract_dir}{preserve_flags}"),
+        (false, Some(members)) => {
+            let recursion = match members.is_recursive {
+                true => "",
+                false => " --no-recursion",
+            };
+            format!(
+                "tar -xvf '{path}' -C {extract_dir}{preserve_flags}{recursion} --null -T {}",
+                members.path
+            )
+        }
+    }
+}
+
+/// The directory every entry is in, e.g. `project-1.2` for `./project-1.2/src/main.rs`
+/// and `./project-1.2/README.md`.
+fn top_level_directory(entries: &[ArchiveEntry]) -> Option<String> {
+    let mut top_level = None;
+    let mut is_directory = false;
+    for entry in entries {
+        let path = path_safety::normalize(&entry.path).ok()?;
+        let (first, rest) = path.split_once('/').unwrap_or((&path, ""));
+        if top_level.get_or_insert_with(|| first.to_owned()) != first {
+            return None;
+        }
+        is_directory |= !rest.is_empty() || entry.kind == ArchiveEntryKind::Directory;
+    }
+    top_level.filter(|_| is_directory)
 }
 
 #[inline]
-pub fn unpack_path(path: &str) -> Result<String, Box<dyn Error>> {
-    let current_dir = env::current_dir()?.display().to_string();
-    let target_dir = format!("{current_dir}/extracted_files");
+fn read_output_dir() -> Option<String> {
+    input_handler::read_optional("Destination directory (leave empty for ./extracted_files):")
+}
 
-    run_command(&format!("mkdir -p {target_dir}"))?;
+/// Absolute path of the directory archives are extracted to.
+pub fn resolve_output_dir(output_dir: Option<&str>) -> Result<String, Box<dyn Error>> {
+    let current_dir = env::current_dir()?;
+    let target_dir = match output_dir {
+        // joining an absolute path replaces the working directory
+        Some(dir) => current_dir.join(dir),
+        None => current_dir.join("extracted_files"),
+    };
+    Ok(target_dir.display().to_string())
+}
 
-    Ok(format!("tar -xvf '{path}' -C '{target_dir}'"))
+/// Entries to extract, in a NUL separated list file.
+struct MemberList {
+    path: String,
+    /// Listed directories bring everything below them along, when the tool recurses into them.
+    is_recursive: bool,
 }
 
-pub fn unpack_all_in_path(path: &str) -> Result<String, Box<dyn Error>> {
-    let path = file_metadata::get_file_metadata(path)?.to_string_path();
-    let path = path.trim_end_matches("/*");
-    let formats = FORMATS_JOINED.as_str();
+/// List of members to extract, or `None` to extract them all, with the plan of how the entries
+/// end up in the target directory. Entries which would be written outside the target directory
+/// are always left out.
+fn select_members(
+    path: &str,
+    entries: &[ArchiveEntry],
+    options: &ExtractOptions,
+    target_dir: &str,
+    rewritten_paths: Option<&HashMap<String, String>>,
+) -> Result<(Option<MemberList>, ConflictPlan), Box<dyn Error>> {
+    let mut safe_entries = path_safety::safe_entries(path, entries)?;
+    safe_entries.retain(|entry| {
+        let is_skipped = links::is_skipped_on_extraction(options.links, entry);
+        if is_skipped {
+            output::status(&format!("Skipping link '{}'.", entry.path));
+        }
+        !is_skipped
+    });
 
-    let files = execute_cmd_get_lines(&format!("ls '{path}' | grep -E '{formats}'"));
-    let archive_paths = files
+    if let Some(rewritten_paths) = rewritten_paths {
+        safe_entries.retain(|entry| rewritten_paths.contains_key(&entry.path));
+    }
+
+    let selected = match options.patterns.is_empty() {
+        // everything was left out by rewriting, an empty archive just extracts nothing
+        true if safe_entries.is_empty() && !entries.is_empty() => {
+            return Err(OperationError::NoEntriesMatched("--strip-components".to_owned()).into());
+        }
+        true => safe_entries,
+        false => {
+            let selected = selection::select_entries(&safe_entries, &options.patterns);
+            if selected.is_empty() {
+                return Err(OperationError::NoEntriesMatched(options.patterns.join(", ")).into());
+            }
+            output::status(&format!("Selected {} entries to extract.", selected.len()));
+            selected
+        }
+    };
+    limits::check_entries(path, &selected)?;
+
+    let is_selected_all = options.patterns.is_empty() && selected.len() == entries.len();
+    let conflict_plan =
+        conflicts::plan(
+            options.on_conflict,
+            selected,
+            target_dir,
+            |entry| match rewritten_paths {
+                Some(rewritten_paths) => rewritten_paths.get(&entry.path).cloned(),
+                None => path_safety::normalize(&entry.path).ok(),
+            },
+        )?;
+    conflicts::print_summary(&conflict_plan);
+    let mut extracted = conflict_plan.extract.clone();
+    if rewritten_paths.is_some() {
+        // renamed entries are moved from the same staging directory
+        extracted.extend(conflict_plan.renamed.iter().map(|(entry, _)| entry.clone()));
+    }
+    let members = match (is_selected_all, conflict_plan.has_exclusions()) {
+        (true, false) => None,
+        (false, false) if !options.patterns.is_empty() => Some(MemberList {
+            path: member_list(path, &extracted),
+            is_recursive: true,
+        }),
+        // listed directories would bring the left out entries back
+        _ => Some(MemberList {
+            path: member_list(path, &extracted),
+            is_recursive: false,
+        }),
+    };
+    Ok((members, conflict_plan))
+}
+
+#[inline]
+fn member_list(archive: &str, entries: &[ArchiveEntry]) -> String {
+    let paths = entries
         .iter()
-        .filter(|file| match string_utils::find_file_extension(file) {
-            Ok(ext) => VALID_ARCHIVE_FORMATS.contains(&&*ext),
-            _ => false,
-        })
-        .map(|file| format!("{path}/{file}"))
+        .map(|entry| entry.path.to_owned())
         .collect::<Vec<String>>();
+    list_file::pending(&unpacker::member_names(archive, &paths))
+}
 
-    println!(
+pub fn unpack_all_in_path(path: &str, options: &ExtractOptions) -> Result<String, Box<dyn Error>> {
+    list_file::finish(unpack_all_cmd(path, options))
+}
+
+fn unpack_all_cmd(path: &str, options: &ExtractOptions) -> Result<String, Box<dyn Error>> {
+    let archive_paths = find_archives_in_path(path)?;
+
+    output::status(&format!(
         "Found {} files to extract: {:?}.",
         archive_paths.len(),
         archive_paths
-    );
+    ));
 
+    let output_dir = resolve_output_dir(options.output_dir.as_deref())?;
+    let mut subdirectories = HashSet::new();
     let mut commands = Vec::new();
+    let mut target_dirs = Vec::new();
+    let mut required_bytes = 0;
     for file in archive_paths {
-        let file = unpack_path(&file)?;
-        commands.push(file);
+        // smart extraction only decides on a directory of their own when the archives share one
+        let archive_options = match options.flat {
+            true => options.clone(),
+            false => ExtractOptions {
+                output_dir: Some(format!(
+                    "{output_dir}/{}",
+                    unique_subdirectory(&file, &mut subdirectories)
+                )),
+                smart: false,
+                ..options.clone()
+            },
+        };
+        let (cmd, target_dir, archive_bytes) = unpack_cmd(&file, &archive_options)?;
+        commands.push(cmd);
+        target_dirs.push(target_dir);
+        required_bytes += archive_bytes;
+    }
+    // all archives are checked together, each one alone could fit
+    disk_space::check(&output_dir, required_bytes, options.force)?;
+    for target_dir in target_dirs {
+        fs::create_dir_all(target_dir)?;
     }
     Ok(commands.join(" && "))
 }
 
+/// File name without the archive extension, including compound ones, e.g. `release` for `./dist/release.tar.gz`.
+fn archive_stem(archive: &str) -> String {
+    let name = archive.rsplit('/').next().unwrap_or(archive);
+    let extension_len = VALID_ARCHIVE_FORMATS
+        .iter()
+        .filter(|format| name.ends_with(*format) && name.len() > format.len())
+        .map(|format| format.len())
+        .max()
+        .unwrap_or(0);
+    name[..name.len() - extension_len].to_owned()
+}
+
+/// Archive name without its extension, e.g. `release` for `./dist/release.tar.gz`. Archives sharing
+/// a name get a numeric suffix, e.g. `release` and `release_1` for `release.zip` and `release.tar.gz`.
+fn unique_subdirectory(archive: &str, taken: &mut HashSet<String>) -> String {
+    let stem = archive_stem(archive);
+    let subdirectory = (0..)
+        .map(|suffix| match suffix {
+            0 => stem.to_owned(),
+            _ => format!("{stem}_{suffix}"),
+        })
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap_or_default();
+    taken.insert(subdirectory.to_owned());
+    subdirectory
+}
+
+/// Archives found directly in the directory, e.g. `./my/dir/a.zip` and `./my/dir/b.tar.gz`.
+pub fn find_archives_in_path(path: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let path = file_metadata::get_file_metadata(path)?.to_string_path();
+    let path = path.trim_end_matches("/*");
+    let formats = FORMATS_JOINED.as_str();
+
+    let files = execute_cmd_get_lines(&format!("ls '{path}' | grep -E '{formats}'"));
+    let archive_paths = files
+        .iter()
+        .filter(|file| is_archive_path(file))
+        .map(|file| format!("{path}/{file}"))
+        .collect::<Vec<String>>();
+    Ok(archive_paths)
+}
+
+/// Checks the extension, including compound ones like `.tar.gz`.
+pub fn is_archive_path(path: &str) -> bool {
+    VALID_ARCHIVE_FORMATS
+        .iter()
+        .any(|format| path.ends_with(format))
+}
+
 pub fn zip(with_password: bool) -> Result<String, Box<dyn Error>> {
     let file_metadata = input_handler::read_path_to_file_or_directory()?;
-    zip_path(&file_metadata, with_password)
+    zip_path(&file_metadata, with_password, &CreateOptions::default())
 }
 
-pub fn zip_path(metadata: &FileMetadata, with_password: bool) -> Result<String, Box<dyn Error>> {
+pub fn zip_path(
+    metadata: &FileMetadata,
+    with_password: bool,
+    options: &CreateOptions,
+) -> Result<String, Box<dyn Error>> {
     let destination_archive = get_clean_archive_name(&metadata.to_short_name()?);
     let encryption = match with_password {
         true => "e",
         false => "",
     };
     let path_to_files = metadata.to_string_path();
-    Ok(format!(
-        "zip -r{encryption} {destination_archive}.zip {path_to_files}"
-    ))
+    check_space_for_archive(&path_to_files, options.deterministic, options)?;
+    let stored = StoredFiles::new(metadata, &options.root)?;
+    let symlinks = stored_symlinks(metadata, &stored)?;
+    let archive = archive_path(&format!("{destination_archive}.zip"), &options.root)?;
+    preserve::report_unsupported(
+        &archive,
+        &preserve::unsupported(true, options.deterministic, &options.preserve),
+    );
+    let cmd = match options.deterministic {
+        true if with_password => return Err(OperationError::DeterministicEncryption.into()),
+        true => reproducible::zip_cmd(
+            &archive,
+            &stored.files,
+            reproducible::source_date_epoch()?,
+            options.links,
+        ),
+        false => {
+            let (link_flags, exclusions) = links::zip_create_flags(options.links, &symlinks);
+            format!(
+                "zip -r{link_flags}{encryption} {} {}{exclusions}",
+                quote_archive(&archive, &options.root),
+                stored.files
+            )
+        }
+    };
+    append_post_create_steps(
+        &cmd,
+        &stored,
+        &archive,
+        &format!("{destination_archive}.zip"),
+        options,
+    )
 }
 
 pub fn tar() -> Result<String, Box<dyn Error>> {
     let file_metadata = input_handler::read_path_to_file_or_directory()?;
-    tar_path(&file_metadata)
+    tar_path(&file_metadata, &CreateOptions::default())
 }
 
-pub fn tar_path(metadata: &FileMetadata) -> Result<String, Box<dyn Error>> {
+pub fn tar_path(
+    metadata: &FileMetadata,
+    options: &CreateOptions,
+) -> Result<String, Box<dyn Error>> {
     let destination_archive = get_clean_archive_name(&metadata.to_short_name()?);
     let path = metadata.to_string_path();
-    let cmd = format!("tar -cf {destination_archive}.tar {path}");
-    Ok(cmd)
+    check_space_for_archive(&path, false, options)?;
+    let stored = StoredFiles::new(metadata, &options.root)?;
+    let link_flags = links::tar_create_flags(options.links, &stored_symlinks(metadata, &stored)?);
+    let archive = archive_path(&format!("{destination_archive}.tar"), &options.root)?;
+    preserve::report_unsupported(
+        &archive,
+        &preserve::unsupported(false, options.deterministic, &options.preserve),
+    );
+    let cmd = match options.deterministic {
+        true => reproducible::tar_cmd(
+            &archive,
+            &stored.files,
+            reproducible::source_date_epoch()?,
+            &link_flags,
+        ),
+        false => format!(
+            "tar -cf {}{link_flags}{} {}",
+            quote_archive(&archive, &options.root),
+            preserve::tar_create_flags(&options.preserve),
+            stored.files
+        ),
+    };
+    append_post_create_steps(
+        &cmd,
+        &stored,
+        &archive,
+        &format!("{destination_archive}.tar"),
+        options,
+    )
+}
+
+/// The archive, created in the working directory, is estimated to be as big as the files. A staged
+/// archive, the deterministic zip, also needs a tar stream and a copy of the files in the temporary
+/// directory.
+fn check_space_for_archive(
+    files: &str,
+    is_staged: bool,
+    options: &CreateOptions,
+) -> Result<(), Box<dyn Error>> {
+    let current_dir = env::current_dir()?.display().to_string();
+    let files_size = disk_space::files_size(files);
+    if !is_staged {
+        return disk_space::check(&current_dir, files_size, options.force);
+    }
+    let temp_dir = env::temp_dir().display().to_string();
+    match disk_space::same_filesystem(&current_dir, &temp_dir) {
+        true => disk_space::check(&current_dir, files_size * 3, options.force),
+        false => {
+            disk_space::check(&temp_dir, files_size * 2, options.force)?;
+            disk_space::check(&current_dir, files_size, options.force)
+        }
+    }
+}
+
+/// Symlinks of the tree, named as the archiving tool sees them.
+#[inline]
+fn stored_symlinks(
+    metadata: &FileMetadata,
+    stored: &StoredFiles,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let symlinks = links::scan_symlinks(metadata)?;
+    Ok(symlinks
+        .iter()
+        .map(|symlink| stored.rebase(symlink))
+        .collect())
+}
+
+/// The archive is created in the working directory; the tool runs elsewhere when the stored
+/// root is changed, so it gets an absolute path then.
+fn archive_path(name: &str, root: &StoredRoot) -> Result<String, Box<dyn Error>> {
+    match root.is_default() {
+        true => Ok(name.to_owned()),
+        false => Ok(env::current_dir()?.join(name).display().to_string()),
+    }
+}
+
+#[inline]
+fn quote_archive(archive: &str, root: &StoredRoot) -> String {
+    match root.is_default() {
+        true => archive.to_owned(),
+        false => shell_quote(archive),
+    }
+}
+
+/// The manifest is written where the archiving tool runs, so it lists the paths as they are stored.
+/// `archive` is the path the tool gets, `archive_name` the one in the working directory.
+fn append_post_create_steps(
+    cmd: &str,
+    stored: &StoredFiles,
+    archive: &str,
+    archive_name: &str,
+    options: &CreateOptions,
+) -> Result<String, Box<dyn Error>> {
+    let cmd = match options.with_manifest {
+        true => format!(
+            "{cmd} && {}",
+            manifest::write_manifest_cmd(&stored.files, archive)
+        ),
+        false => cmd.to_owned(),
+    };
+    let mut steps = vec![stored.wrap_cmd(&cmd)];
+    if let Some(key) = &options.sign_key {
+        signature::ensure_key_exists(key)?;
+        steps.push(signature::sign_cmd(archive_name, key));
+    }
+    Ok(steps.join(" && "))
 }
 
 #[inline]
@@ -115,11 +656,19 @@ pub fn add_to_exising_archive() -> Result<String, Box<dyn Error>> {
 
 pub fn extract_all() -> Result<String, Box<dyn Error>> {
     let path = input_handler::read_path_to_file_or_directory()?.to_string_path();
-    let cmd = unpack_all_in_path(&path)?;
+    let options = ExtractOptions {
+        output_dir: read_output_dir(),
+        ..ExtractOptions::default()
+    };
+    let cmd = unpack_all_in_path(&path, &options)?;
     Ok(cmd)
 }
 
 pub fn extract_all_in_current_dir() -> Result<String, Box<dyn Error>> {
-    let cmd = unpack_all_in_path(".")?;
+    let options = ExtractOptions {
+        output_dir: read_output_dir(),
+        ..ExtractOptions::default()
+    };
+    let cmd = unpack_all_in_path(".", &options)?;
     Ok(cmd)
 }
@@ -1,22 +1,63 @@
 use crate::error::OperationError;
+use crate::output;
 use std::error::Error;
-use std::process::Command;
+use std::io;
+use std::process::{Command, Stdio};
 
+/// Runs the command with inherited output. In JSON modes its stdout is redirected to stderr,
+/// so tools like `tar -v` do not break the structured output.
 pub fn run_command(command: &str) -> Result<(), Box<dyn Error>> {
-    println!("Running command: {command}");
+    output::status(&format!("Running command: {command}"));
     let (arg1, arg2) = get_os_specific_cmd_args();
-    let mut cmd = Command::new(arg1).arg(arg2).arg(command).spawn()?;
-    let code = cmd.wait()?.code();
-    if code.is_some() {
-        let code = code.unwrap();
-        if code != 0 {
-            return Err(OperationError::FailedToRunCommand(format!(
-                "Command '{command}' failed with code: {code}"
+    let mut cmd = Command::new(arg1);
+    cmd.arg(arg2).arg(command);
+    if output::is_machine_readable() {
+        cmd.stdout(Stdio::from(io::stderr()));
+    }
+    let mut cmd = cmd.spawn()?;
+    if let Some(code) = cmd.wait()?.code()
+        && code != 0
+    {
+        return Err(OperationError::FailedToRunCommand(format!(
+            "Command '{command}' failed with code: {code}"
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Runs the command with stdout always passed through, for operations whose output is the data itself.
+pub fn stream_command(command: &str) -> Result<(), Box<dyn Error>> {
+    let (arg1, arg2) = get_os_specific_cmd_args();
+    let status = Command::new(arg1).arg(arg2).arg(command).status()?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(OperationError::FailedToRunCommand(format!(
+            "Command '{command}' failed with {status}"
+        ))
+        .into()),
+    }
+}
+
+/// Runs the command without printing it or its output, which is passed on in the error when it fails.
+pub fn execute_cmd(command: &str) -> Result<(), Box<dyn Error>> {
+    let (arg1, arg2) = get_os_specific_cmd_args();
+    let output = Command::new(arg1).arg(arg2).arg(command).output()?;
+    match output.status.success() {
+        true => Ok(()),
+        false => {
+            // some tools, like `unzip -t`, report problems on stdout
+            let message = match output.stderr.is_empty() {
+                true => String::from_utf8_lossy(&output.stdout),
+                false => String::from_utf8_lossy(&output.stderr),
+            };
+            Err(OperationError::FailedToRunCommand(format!(
+                "Command '{command}' failed: {}",
+                message.trim()
             ))
-            .into());
+            .into())
         }
     }
-    Ok(())
 }
 
 pub fn execute_cmd_get_lines(cmd: &str) -> Vec<String> {
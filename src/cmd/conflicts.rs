@@ -0,0 +1,289 @@
+use crate::archive::archive_entry::{ArchiveEntry, ArchiveEntryKind};
+use crate::archive::unpacker;
+use crate::cmd::command_runner::execute_cmd_get_lines;
+use crate::error::OperationError;
+use crate::file::list_file;
+use crate::file::string_utils::shell_quote;
+use crate::input_handler;
+use crate::output;
+use std::error::Error;
+use std::path::Path;
+
+/// What happens to an entry whose destination file already exists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    #[default]
+    Overwrite,
+    Skip,
+    /// Overwrites only when the entry is newer than the existing file.
+    KeepNewer,
+    /// Extracts next to the existing file with a numeric suffix, e.g. `app_1.yml`.
+    Rename,
+    Ask,
+}
+
+pub fn parse_conflict_policy(value: &str) -> Result<ConflictPolicy, Box<dyn Error>> {
+    match value {
+        "overwrite" => Ok(ConflictPolicy::Overwrite),
+        "skip" => Ok(ConflictPolicy::Skip),
+        "keep-newer" => Ok(ConflictPolicy::KeepNewer),
+        "rename" => Ok(ConflictPolicy::Rename),
+        "ask" => Ok(ConflictPolicy::Ask),
+        _ => Err(OperationError::InvalidArgument(format!("--on-conflict {value}")).into()),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Resolution {
+    Overwrite,
+    Skip,
+    Rename,
+}
+
+/// Entries split by how they are extracted into the target directory.
+#[derive(Debug, Default)]
+pub struct ConflictPlan {
+    pub extract: Vec<ArchiveEntry>,
+    pub overwritten: Vec<ArchiveEntry>,
+    pub skipped: Vec<ArchiveEntry>,
+    /// Entries with the path they are extracted to instead.
+    pub renamed: Vec<(ArchiveEntry, String)>,
+}
+
+impl ConflictPlan {
+    /// Whether some entries have to be left out of the regular extraction.
+    #[inline]
+    pub fn has_exclusions(&self) -> bool {
+        !self.skipped.is_empty() || !self.renamed.is_empty()
+    }
+}
+
+/// Decides, entry by entry, what to do with files already present in `target_dir`, where each entry
+/// is written to its `relative_path`. Directories are merged, they never conflict.
+pub fn plan(
+    policy: ConflictPolicy,
+    entries: Vec<ArchiveEntry>,
+    target_dir: &str,
+    relative_path: impl Fn(&ArchiveEntry) -> Option<String>,
+) -> Result<ConflictPlan, Box<dyn Error>> {
+    let mut plan = ConflictPlan::default();
+    for entry in entries {
+        let destination = match relative_path(&entry) {
+            Some(path) => format!("{target_dir}/{path}"),
+            None => {
+                plan.extract.push(entry);
+                continue;
+            }
+        };
+        let exists = Path::new(&destination).symlink_metadata().is_ok();
+        if entry.kind == ArchiveEntryKind::Directory || !exists {
+            plan.extract.push(entry);
+            continue;
+        }
+        match resolve(policy, &entry, &destination)? {
+            Resolution::Overwrite => {
+                plan.overwritten.push(entry.clone());
+                plan.extract.push(entry);
+            }
+            Resolution::Skip => plan.skipped.push(entry),
+            Resolution::Rename => {
+                let renamed = free_path(&destination);
+                plan.renamed.push((entry, renamed));
+            }
+        }
+    }
+    Ok(plan)
+}
+
+fn resolve(
+    policy: ConflictPolicy,
+    entry: &ArchiveEntry,
+    destination: &str,
+) -> Result<Resolution, Box<dyn Error>> {
+    match policy {
+        ConflictPolicy::Overwrite => Ok(Resolution::Overwrite),
+        ConflictPolicy::Skip => Ok(Resolution::Skip),
+        ConflictPolicy::Rename => Ok(Resolution::Rename),
+        ConflictPolicy::KeepNewer => match is_newer(&entry.mtime, &modified_time(destination)) {
+            true => Ok(Resolution::Overwrite),
+            false => Ok(Resolution::Skip),
+        },
+        ConflictPolicy::Ask => {
+            let prompt = format!(
+                "'{destination}' already exists - [o]verwrite, [s]kip or [r]ename (default skip):"
+            );
+            match input_handler::read_optional(&prompt).as_deref() {
+                Some("o") => Ok(Resolution::Overwrite),
+                Some("r") => Ok(Resolution::Rename),
+                _ => Ok(Resolution::Skip),
+            }
+        }
+    }
+}
+
+/// Local time of the last modification, in the `YYYY-MM-DD HH:MM:SS` form archive listings use.
+fn modified_time(path: &str) -> String {
+    execute_cmd_get_lines(&format!(
+        "date -r {} '+%Y-%m-%d %H:%M:%S'",
+        shell_quote(path)
+    ))
+    .into_iter()
+    .next()
+    .unwrap_or_default()
+}
+
+/// Compares ISO-like timestamps up to the precision of the less precise one. Listings without a year,
+/// like `Oct 19 06:18`, can not be compared and never count as newer.
+fn is_newer(entry_mtime: &str, file_mtime: &str) -> bool {
+    let is_iso = |time: &str| time.len() >= 16 && time.as_bytes()[4] == b'-';
+    if !is_iso(entry_mtime) || !is_iso(file_mtime) {
+        return false;
+    }
+    let precision = entry_mtime.len().min(file_mtime.len());
+    entry_mtime[..precision] > file_mtime[..precision]
+}
+
+/// First free path with a numeric suffix before the extension, e.g. `conf/app_1.yml`, `conf/app_2.yml`.
+fn free_path(destination: &str) -> String {
+    let path = Path::new(destination);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    let parent = path
+        .parent()
+        .map(|parent| parent.display().to_string())
+        .unwrap_or_default();
+    (1..)
+        .map(|suffix| format!("{parent}/{stem}_{suffix}{extension}"))
+        .find(|candidate| Path::new(candidate).symlink_metadata().is_err())
+        .unwrap_or_default()
+}
+
+/// Extracts the renamed entries to a hidden staging directory inside `extract_dir` (a shell word) and
+/// moves them to their new paths, relative to `extract_dir`. `flags` are added to the `tar` command.
+pub fn extract_renamed_cmd(
+    archive: &str,
+    flags: &str,
+    renamed: &[(ArchiveEntry, String)],
+    extract_dir: &str,
+) -> Option<String> {
+    if renamed.is_empty() {
+        return None;
+    }
+    let members = renamed
+        .iter()
+        .map(|(entry, _)| entry.path.to_owned())
+        .collect::<Vec<String>>();
+    let moves = renamed
+        .iter()
+        .map(|(entry, destination)| (entry.path.to_owned(), destination.to_owned()))
+        .collect::<Vec<(String, String)>>();
+    let member_list = list_file::pending(&unpacker::member_names(archive, &members));
+    Some(format!(
+        "(renamed_staging= && trap 'rm -rf \"$renamed_staging\"' EXIT && trap 'exit 130' INT TERM \
+        && renamed_staging=$(mktemp -d {extract_dir}/'.renamed.XXXXXX') && {} && {})",
+        unpacker::extract_members_cmd(archive, "\"$renamed_staging\"", &member_list, flags),
+        list_file::moves_cmd("\"$renamed_staging\"", &moves)
+    ))
+}
+
+pub fn print_summary(plan: &ConflictPlan) {
+    if !plan.overwritten.is_empty() {
+        output::status(&format!(
+            "Overwriting {} existing files.",
+            plan.overwritten.len()
+        ));
+    }
+    for entry in &plan.skipped {
+        output::status(&format!(
+            "Skipped '{}', the file already exists.",
+            entry.path
+        ));
+    }
+    for (entry, destination) in &plan.renamed {
+        output::status(&format!("Renamed '{}' to '{destination}'.", entry.path));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::archive_entry::test_entry;
+    use crate::archive::path_safety;
+    use crate::file::temp_dir::TempDir;
+    use std::fs;
+
+    fn entry(path: &str, mtime: &str) -> ArchiveEntry {
+        ArchiveEntry {
+            mtime: mtime.to_owned(),
+            ..test_entry(path, ArchiveEntryKind::File)
+        }
+    }
+
+    #[test]
+    fn should_compare_times() {
+        assert!(is_newer("2025-07-14 17:52", "2025-07-14 17:51:59"));
+        assert!(!is_newer("2025-07-14 17:51:00", "2025-07-14 17:51:30"));
+        assert!(!is_newer("Oct 19 06:18", "2025-07-14 17:51:30"));
+    }
+
+    fn relative_path(entry: &ArchiveEntry) -> Option<String> {
+        path_safety::normalize(&entry.path).ok()
+    }
+
+    #[test]
+    fn should_plan_conflicts() {
+        let dir = TempDir::new("conflicts_test").unwrap();
+        let target_dir = dir.to_string_path();
+        fs::create_dir_all(dir.path().join("conf")).unwrap();
+        fs::write(dir.path().join("conf/app.yml"), "old").unwrap();
+        fs::write(dir.path().join("conf/app_1.yml"), "old").unwrap();
+        let entries = || {
+            vec![
+                entry("./conf/app.yml", "2000-01-01 00:00"),
+                entry("./conf/db.yml", "2000-01-01 00:00"),
+            ]
+        };
+
+        let plan_skip = plan(ConflictPolicy::Skip, entries(), &target_dir, relative_path).unwrap();
+        assert_eq!(plan_skip.extract.len(), 1);
+        assert_eq!(plan_skip.skipped[0].path, "./conf/app.yml");
+
+        let plan_newer = plan(
+            ConflictPolicy::KeepNewer,
+            entries(),
+            &target_dir,
+            relative_path,
+        )
+        .unwrap();
+        assert_eq!(plan_newer.skipped.len(), 1);
+
+        let plan_rename = plan(
+            ConflictPolicy::Rename,
+            entries(),
+            &target_dir,
+            relative_path,
+        )
+        .unwrap();
+        assert_eq!(
+            plan_rename.renamed[0].1,
+            format!("{target_dir}/conf/app_2.yml")
+        );
+        let renamed_cmd = extract_renamed_cmd("a.tar", "", &plan_rename.renamed, "'/out'").unwrap();
+        assert!(renamed_cmd.contains("--no-recursion --null -T '"));
+        assert!(!renamed_cmd.contains("app.yml"));
+        assert!(list_file::finish(Err("not run".into())).is_err());
+
+        let plan_overwrite = plan(
+            ConflictPolicy::Overwrite,
+            entries(),
+            &target_dir,
+            relative_path,
+        )
+        .unwrap();
+        assert_eq!(plan_overwrite.extract.len(), 2);
+        assert!(!plan_overwrite.has_exclusions());
+    }
+}
@@ -0,0 +1,244 @@
+use crate::archive::archive_entry::{ArchiveEntry, ArchiveEntryKind};
+use crate::archive::path_safety;
+use crate::error::OperationError;
+use crate::file::file_metadata::FileMetadata;
+use crate::file::string_utils::shell_quote;
+use crate::output;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// How symlinks and hardlinks are handled when an archive is created or extracted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkMode {
+    /// Links are stored and extracted as links.
+    Preserve,
+    /// Symlinks are replaced by the content they point to; hardlinks stay links.
+    Follow,
+    /// Every link, hardlinks included, becomes an independent copy.
+    Copy,
+    /// Links are left out.
+    Skip,
+}
+
+pub fn parse_link_mode(value: &str) -> Result<LinkMode, Box<dyn Error>> {
+    match value {
+        "preserve" => Ok(LinkMode::Preserve),
+        "follow" | "dereference" => Ok(LinkMode::Follow),
+        "copy" => Ok(LinkMode::Copy),
+        "skip" => Ok(LinkMode::Skip),
+        _ => Err(OperationError::InvalidArgument(format!("--links {value}")).into()),
+    }
+}
+
+/// Symlinks in the tree being archived, named the way `tar` and `zip` name them, e.g. `./my/dir/current`.
+/// Links pointing outside the tree are reported - they dangle when preserved and pull outside content
+/// in when followed.
+pub fn scan_symlinks(metadata: &FileMetadata) -> Result<Vec<String>, Box<dyn Error>> {
+    let root = Path::new(&metadata.path);
+    let mut symlinks = Vec::new();
+    match metadata.is_directory {
+        true => collect_symlinks(root, &mut symlinks)?,
+        false if root.is_symlink() => symlinks.push(metadata.path.to_owned()),
+        false => {}
+    }
+
+    for symlink in &symlinks {
+        let target = fs::read_link(symlink)?.display().to_string();
+        let relative_target = Path::new(symlink)
+            .parent()
+            .and_then(|parent| parent.strip_prefix(root).ok())
+            .map(|parent| parent.join(&target).display().to_string())
+            .unwrap_or_else(|| target.to_owned());
+        let is_outside =
+            target.starts_with('/') || path_safety::normalize(&relative_target).is_err();
+        if is_outside {
+            output::status(&format!(
+                "Link '{symlink}' points outside the archived tree: '{target}'."
+            ));
+        }
+    }
+    Ok(symlinks)
+}
+
+/// Does not descend into symlinked directories.
+fn collect_symlinks(dir: &Path, symlinks: &mut Vec<String>) -> Result<(), Box<dyn Error>> {
+    for dir_entry in fs::read_dir(dir)? {
+        let path = dir_entry?.path();
+        let file_type = fs::symlink_metadata(&path)?.file_type();
+        if file_type.is_symlink() {
+            symlinks.push(path.display().to_string());
+        } else if file_type.is_dir() {
+            collect_symlinks(&path, symlinks)?;
+        }
+    }
+    Ok(())
+}
+
+/// Options added to `tar -c`; nothing keeps the tar default, which preserves links.
+pub fn tar_create_flags(mode: Option<LinkMode>, symlinks: &[String]) -> String {
+    match mode {
+        None | Some(LinkMode::Preserve) => String::new(),
+        Some(LinkMode::Follow) => " --dereference".to_owned(),
+        Some(LinkMode::Copy) => " --dereference --hard-dereference".to_owned(),
+        Some(LinkMode::Skip) if symlinks.is_empty() => String::new(),
+        Some(LinkMode::Skip) => {
+            let exclusions = symlinks
+                .iter()
+                .map(|symlink| format!(" --exclude={}", shell_quote(symlink)))
+                .collect::<String>();
+            format!(" --no-wildcards{exclusions}")
+        }
+    }
+}
+
+/// Flag letters added to `zip -r` and exclusions appended after the files; nothing keeps the zip default,
+/// which follows links. `-y` stores a symlink as a link, with the Unix mode in the external attributes;
+/// skipping needs it too, as `-x` leaves out the link but `zip` would still descend into a linked directory.
+pub fn zip_create_flags(mode: Option<LinkMode>, symlinks: &[String]) -> (&'static str, String) {
+    match mode {
+        Some(LinkMode::Preserve) => ("y", String::new()),
+        Some(LinkMode::Skip) if !symlinks.is_empty() => {
+            let exclusions = symlinks
+                .iter()
+                .map(|symlink| shell_quote(symlink))
+                .collect::<Vec<String>>()
+                .join(" ");
+            ("y", format!(" -x {exclusions}"))
+        }
+        _ => ("", String::new()),
+    }
+}
+
+/// Link entries are left out of extraction in skip mode.
+#[inline]
+pub fn is_skipped_on_extraction(mode: Option<LinkMode>, entry: &ArchiveEntry) -> bool {
+    mode == Some(LinkMode::Skip)
+        && matches!(
+            entry.kind,
+            ArchiveEntryKind::Symlink | ArchiveEntryKind::Hardlink
+        )
+}
+
+/// Steps run after extraction to `target_dir` (a shell word), replacing the extracted links by copies
+/// of their targets. Dangling links are reported and kept as they are.
+pub fn materialize_links_cmd(
+    mode: Option<LinkMode>,
+    entries: &[ArchiveEntry],
+    target_dir: &str,
+) -> Option<String> {
+    let steps = entries
+        .iter()
+        .filter(|entry| {
+            matches!(
+                (mode, entry.kind),
+                (
+                    Some(LinkMode::Follow | LinkMode::Copy),
+                    ArchiveEntryKind::Symlink
+                ) | (Some(LinkMode::Copy), ArchiveEntryKind::Hardlink)
+            )
+        })
+        .filter_map(|entry| path_safety::normalize(&entry.path).ok())
+        .map(|path| {
+            let link = format!("{target_dir}/{}", shell_quote(&path));
+            let copy = format!("{target_dir}/{}", shell_quote(&format!("{path}.link-copy")));
+            let report = shell_quote(&format!("Skipping dangling link '{path}'."));
+            format!(
+                "if [ -e {link} ]; then cp -RL {link} {copy} && rm -f {link} && mv {copy} {link}; \
+                else echo {report} >&2; fi"
+            )
+        })
+        .collect::<Vec<String>>();
+    match steps.is_empty() {
+        true => None,
+        false => Some(steps.join(" && ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::archive_entry::test_entry;
+    use crate::cmd::command_runner::{execute_cmd, execute_cmd_get_lines};
+    use crate::file::temp_dir::TempDir;
+
+    fn link(path: &str, kind: ArchiveEntryKind) -> ArchiveEntry {
+        ArchiveEntry {
+            mode: "lrwxrwxrwx".to_owned(),
+            link_target: Some("app.yml".to_owned()),
+            ..test_entry(path, kind)
+        }
+    }
+
+    #[test]
+    fn should_build_create_flags() {
+        let symlinks = vec!["./dir/current".to_owned()];
+        assert_eq!(tar_create_flags(None, &symlinks), "");
+        assert_eq!(
+            tar_create_flags(Some(LinkMode::Copy), &symlinks),
+            " --dereference --hard-dereference"
+        );
+        assert_eq!(
+            tar_create_flags(Some(LinkMode::Skip), &symlinks),
+            " --no-wildcards --exclude='./dir/current'"
+        );
+        assert_eq!(
+            zip_create_flags(Some(LinkMode::Preserve), &symlinks),
+            ("y", String::new())
+        );
+        assert_eq!(
+            zip_create_flags(Some(LinkMode::Skip), &symlinks),
+            ("y", " -x './dir/current'".to_owned())
+        );
+    }
+
+    #[test]
+    fn should_skip_linked_directories_in_zip() {
+        let dir = TempDir::new("links_test").unwrap();
+        fs::create_dir_all(dir.path().join("t/real")).unwrap();
+        fs::write(dir.path().join("t/real/a.txt"), "a").unwrap();
+        std::os::unix::fs::symlink("real", dir.path().join("t/dirlink")).unwrap();
+        std::os::unix::fs::symlink("real/a.txt", dir.path().join("t/flink")).unwrap();
+
+        let symlinks = vec!["./t/dirlink".to_owned(), "./t/flink".to_owned()];
+        let (link_flags, exclusions) = zip_create_flags(Some(LinkMode::Skip), &symlinks);
+        let root = shell_quote(&dir.to_string_path());
+        execute_cmd(&format!(
+            "cd {root} && zip -qr{link_flags} a.zip ./t{exclusions}"
+        ))
+        .unwrap();
+        let stored = execute_cmd_get_lines(&format!("cd {root} && unzip -Z1 a.zip"));
+        assert_eq!(stored, vec!["t/", "t/real/", "t/real/a.txt"]);
+    }
+
+    #[test]
+    fn should_materialize_links() {
+        let entries = vec![
+            link("./conf/current.yml", ArchiveEntryKind::Symlink),
+            link("./conf/hard.yml", ArchiveEntryKind::Hardlink),
+        ];
+        let copy = materialize_links_cmd(Some(LinkMode::Copy), &entries, "'/out'").unwrap();
+        assert!(copy.contains("'/out'/'conf/hard.yml'"));
+
+        let dir = TempDir::new("links_test").unwrap();
+        let out = shell_quote(&dir.to_string_path());
+        fs::create_dir_all(dir.path().join("conf")).unwrap();
+        fs::write(dir.path().join("conf/app.yml"), "app").unwrap();
+        std::os::unix::fs::symlink("app.yml", dir.path().join("conf/current.yml")).unwrap();
+        std::os::unix::fs::symlink("missing.yml", dir.path().join("conf/dangling.yml")).unwrap();
+        let follow = vec![
+            link("./conf/current.yml", ArchiveEntryKind::Symlink),
+            link("./conf/dangling.yml", ArchiveEntryKind::Symlink),
+        ];
+        execute_cmd(&materialize_links_cmd(Some(LinkMode::Follow), &follow, &out).unwrap())
+            .unwrap();
+        let current = dir.path().join("conf/current.yml");
+        assert!(!current.is_symlink());
+        assert_eq!(fs::read_to_string(current).unwrap(), "app");
+        assert!(dir.path().join("conf/dangling.yml").is_symlink());
+        assert!(materialize_links_cmd(Some(LinkMode::Preserve), &entries, "'/out'").is_none());
+
+        assert!(is_skipped_on_extraction(Some(LinkMode::Skip), &entries[1]));
+        assert!(!is_skipped_on_extraction(None, &entries[1]));
+    }
+}
@@ -0,0 +1,140 @@
+use crate::archive::unpacker;
+use crate::error::OperationError;
+use crate::file::file_metadata::FileMetadata;
+use crate::file::string_utils::shell_quote;
+use crate::input_handler;
+use std::error::Error;
+use std::fs;
+
+const MANIFEST_EXTENSION: &str = ".sha256";
+
+#[inline]
+pub fn manifest_path_for(archive: &str) -> String {
+    format!("{archive}{MANIFEST_EXTENSION}")
+}
+
+/// Hashes every regular file matched by `files` and writes a `sha256sum` compatible manifest next to the archive.
+/// Leading slashes are dropped from the listed paths, the same way `zip` and `tar` store absolute paths.
+pub fn write_manifest_cmd(files: &str, archive: &str) -> String {
+    let manifest = shell_quote(&manifest_path_for(archive));
+    format!(
+        "find {files} -type f -exec sha256sum {{}} + | sed 's#  /#  #' | LC_ALL=C sort -k2 > {manifest}"
+    )
+}
+
+pub fn verify() -> Result<String, Box<dyn Error>> {
+    let target = input_handler::read_path_to_file_or_directory()?;
+    let manifest = input_handler::read_optional(
+        "Provide manifest path (leave empty to use '<archive>.sha256'):",
+    );
+    verify_manifest(&target, manifest.as_deref())
+}
+
+/// Checks an archive or an already extracted directory against a manifest created with [`write_manifest_cmd`].
+/// Archives are extracted into a temporary directory which is removed once the check is done.
+pub fn verify_manifest(
+    target: &FileMetadata,
+    manifest: Option<&str>,
+) -> Result<String, Box<dyn Error>> {
+    let manifest = match (manifest, target.is_directory) {
+        (Some(manifest), _) => manifest.to_owned(),
+        (None, false) => manifest_path_for(&target.path),
+        (None, true) => return Err(OperationError::ManifestNotProvided.into()),
+    };
+    let manifest = match fs::canonicalize(&manifest) {
+        Ok(manifest) => shell_quote(&manifest.display().to_string()),
+        Err(_) => return Err(OperationError::ManifestDoesNotExist(manifest).into()),
+    };
+
+    let cmd = match target.is_directory {
+        true => format!(
+            "cd {} && sha256sum -c {manifest}",
+            shell_quote(&target.path)
+        ),
+        false => format!(
+            "tmp=$(mktemp -d) && {} && (cd \"$tmp\" && sha256sum -c {manifest}); \
+            status=$?; rm -rf \"$tmp\"; exit $status",
+            unpacker::extract_cmd(&target.path, "\"$tmp\"")
+        ),
+    };
+    Ok(cmd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::command_builder::{self, CreateOptions};
+    use crate::cmd::command_runner::execute_cmd;
+    use crate::cmd::stored_root::StoredRoot;
+    use crate::file::temp_dir::TempDir;
+
+    #[test]
+    fn should_verify_created_zip() {
+        let dir = TempDir::new("manifest_test").unwrap();
+        fs::create_dir_all(dir.path().join("data/sub")).unwrap();
+        fs::write(dir.path().join("data/a.txt"), "a").unwrap();
+        fs::write(dir.path().join("data/sub/b.txt"), "b").unwrap();
+        let data = FileMetadata {
+            path: dir.path().join("data").display().to_string(),
+            wildcard: None,
+            is_directory: true,
+        };
+        let options = CreateOptions {
+            with_manifest: true,
+            ..CreateOptions::default()
+        };
+        let root = shell_quote(&dir.to_string_path());
+        let create = command_builder::zip_path(&data, false, &options).unwrap();
+        execute_cmd(&format!("cd {root} && {create}")).unwrap();
+
+        let archive = FileMetadata {
+            path: dir.path().join("data_archive.zip").display().to_string(),
+            wildcard: None,
+            is_directory: false,
+        };
+        execute_cmd(&verify_manifest(&archive, None).unwrap()).unwrap();
+
+        fs::write(
+            dir.path().join("data_archive.zip.sha256"),
+            "0  data/a.txt\n",
+        )
+        .unwrap();
+        assert!(execute_cmd(&verify_manifest(&archive, None).unwrap()).is_err());
+    }
+
+    #[test]
+    fn should_verify_archive_with_stored_root() {
+        let dir = TempDir::new("manifest_root_test").unwrap();
+        fs::write(dir.path().join("a.txt"), "a").unwrap();
+        let data = FileMetadata {
+            path: dir.to_string_path(),
+            wildcard: None,
+            is_directory: true,
+        };
+        // the archive is created in the working directory
+        let archive = format!(
+            "{}_archive.tar",
+            data.to_short_name().unwrap().replace('.', "_")
+        );
+        for prefix in [None, Some("project-1.2".to_owned())] {
+            let options = CreateOptions {
+                with_manifest: true,
+                root: StoredRoot {
+                    base_dir: Some(dir.to_string_path()),
+                    prefix,
+                },
+                ..CreateOptions::default()
+            };
+            execute_cmd(&command_builder::tar_path(&data, &options).unwrap()).unwrap();
+            let target = FileMetadata {
+                path: archive.to_owned(),
+                wildcard: None,
+                is_directory: false,
+            };
+            let result = execute_cmd(&verify_manifest(&target, None).unwrap());
+            fs::remove_file(&archive).unwrap();
+            fs::remove_file(manifest_path_for(&archive)).unwrap();
+            assert!(result.is_ok(), "{result:?}");
+        }
+    }
+}
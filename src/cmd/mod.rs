@@ -1,3 +1,12 @@
 pub mod arg_parser;
+pub mod atomic;
 pub mod command_builder;
 pub mod command_runner;
+pub mod conflicts;
+pub mod links;
+pub mod manifest;
+pub mod preserve;
+pub mod reproducible;
+pub mod rewrite;
+pub mod signature;
+pub mod stored_root;
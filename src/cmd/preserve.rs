@@ -0,0 +1,227 @@
+use crate::error::OperationError;
+use crate::output;
+use std::error::Error;
+
+/// File metadata which can be stored in an archive and restored on extraction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attribute {
+    /// Unix permission bits.
+    Mode,
+    /// Owner and group, by name with the numeric ids as fallback.
+    Owner,
+    /// Owner and group by numeric uid/gid only, ignoring the names.
+    NumericOwner,
+    /// Modification time with nanosecond precision.
+    Mtime,
+    Xattrs,
+    Acls,
+}
+
+impl Attribute {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Attribute::Mode => "mode",
+            Attribute::Owner => "owner",
+            Attribute::NumericOwner => "numeric-owner",
+            Attribute::Mtime => "mtime",
+            Attribute::Xattrs => "xattrs",
+            Attribute::Acls => "acls",
+        }
+    }
+}
+
+const ALL_ATTRIBUTES: [Attribute; 5] = [
+    Attribute::Mode,
+    Attribute::Owner,
+    Attribute::Mtime,
+    Attribute::Xattrs,
+    Attribute::Acls,
+];
+
+/// Comma separated attributes, e.g. `mode,mtime`, or `all`.
+pub fn parse_attributes(value: &str) -> Result<Vec<Attribute>, Box<dyn Error>> {
+    let mut attributes = Vec::new();
+    for name in value.split(',').map(str::trim) {
+        let parsed = match name {
+            "all" => ALL_ATTRIBUTES.to_vec(),
+            _ => match ALL_ATTRIBUTES
+                .iter()
+                .chain([Attribute::NumericOwner].iter())
+                .find(|attribute| attribute.name() == name)
+            {
+                Some(attribute) => vec![*attribute],
+                None => {
+                    return Err(
+                        OperationError::InvalidArgument(format!("--preserve {value}")).into(),
+                    );
+                }
+            },
+        };
+        for attribute in parsed {
+            if !attributes.contains(&attribute) {
+                attributes.push(attribute);
+            }
+        }
+    }
+    Ok(attributes)
+}
+
+/// Options added to `tar -c`. Nanosecond times, xattrs and ACLs need PAX records of the POSIX format;
+/// mode and owner names are always stored.
+pub fn tar_create_flags(attributes: &[Attribute]) -> String {
+    let mut flags = String::new();
+    let needs_pax = attributes.iter().any(|attribute| {
+        matches!(
+            attribute,
+            Attribute::Mtime | Attribute::Xattrs | Attribute::Acls
+        )
+    });
+    if needs_pax {
+        flags.push_str(" --format=posix");
+    }
+    for attribute in attributes {
+        match attribute {
+            Attribute::NumericOwner => flags.push_str(" --numeric-owner"),
+            Attribute::Xattrs => flags.push_str(" --xattrs"),
+            Attribute::Acls => flags.push_str(" --acls"),
+            _ => {}
+        }
+    }
+    flags
+}
+
+/// Options added to `tar -x`; owners are restored only when running as root. ZIP archives get none,
+/// what they can not restore is reported by `unsupported_on_extraction`.
+pub fn extract_flags(is_zip: bool, attributes: &[Attribute]) -> String {
+    if is_zip {
+        return String::new();
+    }
+    attributes
+        .iter()
+        .map(|attribute| match attribute {
+            Attribute::Mode => " --preserve-permissions",
+            Attribute::Owner => " --same-owner",
+            Attribute::NumericOwner => " --same-owner --numeric-owner",
+            // times are restored by default
+            Attribute::Mtime => "",
+            Attribute::Xattrs => " --xattrs",
+            Attribute::Acls => " --acls",
+        })
+        .collect()
+}
+
+/// Attributes the created archive can not carry, with the reason. ZIP keeps the Unix mode in its
+/// external attributes and the numeric uid/gid in the Unix extra field (`zip -X` leaves it out),
+/// but nothing more. Deterministic archives normalise or leave out every attribute.
+pub fn unsupported(
+    is_zip: bool,
+    deterministic: bool,
+    attributes: &[Attribute],
+) -> Vec<(Attribute, &'static str)> {
+    attributes
+        .iter()
+        .filter_map(|attribute| {
+            let reason = match (attribute, deterministic, is_zip) {
+                (Attribute::Xattrs | Attribute::Acls, _, true) => "ZIP has no field for it",
+                (_, true, _) => "deterministic archives normalise it",
+                (Attribute::Owner, _, true) => "ZIP stores numeric uid/gid only, without names",
+                (Attribute::Mtime, _, true) => "ZIP stores times with 1 second precision",
+                _ => return None,
+            };
+            Some((*attribute, reason))
+        })
+        .collect()
+}
+
+/// Attributes not restored when extracting: ZIP archives are extracted without the `tar` options.
+pub fn unsupported_on_extraction(
+    is_zip: bool,
+    attributes: &[Attribute],
+) -> Vec<(Attribute, &'static str)> {
+    if !is_zip {
+        return Vec::new();
+    }
+    attributes
+        .iter()
+        .filter_map(|attribute| {
+            let reason = match attribute {
+                Attribute::Mode => return None,
+                Attribute::Owner | Attribute::NumericOwner => {
+                    "owners are not restored from ZIP archives"
+                }
+                Attribute::Mtime => "ZIP stores times with 1 second precision",
+                Attribute::Xattrs | Attribute::Acls => "ZIP has no field for it",
+            };
+            Some((*attribute, reason))
+        })
+        .collect()
+}
+
+pub fn report_unsupported(archive: &str, unsupported: &[(Attribute, &'static str)]) {
+    for (attribute, reason) in unsupported {
+        output::status(&format!(
+            "'{archive}' does not preserve {}: {reason}.",
+            attribute.name()
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::command_runner::{execute_cmd, execute_cmd_get_lines};
+    use crate::file::string_utils::shell_quote;
+    use crate::file::temp_dir::TempDir;
+
+    #[test]
+    fn should_parse_attributes() {
+        assert_eq!(
+            parse_attributes("mode,mtime,mode").unwrap(),
+            vec![Attribute::Mode, Attribute::Mtime]
+        );
+        assert_eq!(parse_attributes("all").unwrap().len(), 5);
+        assert!(parse_attributes("mode,colour").is_err());
+    }
+
+    #[test]
+    fn should_build_flags_and_report_unsupported() {
+        let attributes = parse_attributes("numeric-owner,xattrs,acls").unwrap();
+        assert_eq!(
+            tar_create_flags(&attributes),
+            " --format=posix --numeric-owner --xattrs --acls"
+        );
+        assert_eq!(
+            extract_flags(false, &attributes),
+            " --same-owner --numeric-owner --xattrs --acls"
+        );
+        assert_eq!(extract_flags(true, &attributes), "");
+        assert!(unsupported(false, false, &attributes).is_empty());
+        assert_eq!(unsupported(true, false, &attributes).len(), 2);
+        assert_eq!(
+            unsupported(false, true, &[Attribute::Mtime]),
+            vec![(Attribute::Mtime, "deterministic archives normalise it")]
+        );
+        assert_eq!(unsupported(false, true, &attributes).len(), 3);
+        assert!(unsupported_on_extraction(false, &attributes).is_empty());
+        assert_eq!(unsupported_on_extraction(true, &attributes).len(), 3);
+        assert!(unsupported_on_extraction(true, &[Attribute::Mode]).is_empty());
+    }
+
+    #[test]
+    fn should_store_mode_and_owner_in_zip() {
+        let dir = TempDir::new("preserve_test").unwrap();
+        let root = shell_quote(&dir.to_string_path());
+        execute_cmd(&format!(
+            "cd {root} && printf a > f && chmod 640 f && zip -q a.zip f && zip -qX x.zip f"
+        ))
+        .unwrap();
+        let details = |archive: &str| {
+            execute_cmd_get_lines(&format!("cd {root} && zipinfo -v {archive}")).join("\n")
+        };
+        // the Unix extra field carries uid/gid, -X (deterministic archives) leaves it out
+        let zip = details("a.zip");
+        assert!(zip.contains("Unix file attributes (100640 octal)"));
+        assert!(zip.contains("0x7875 (Unix UID/GID"));
+        assert!(!details("x.zip").contains("0x7875"));
+    }
+}
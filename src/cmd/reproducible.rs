@@ -0,0 +1,132 @@
+use crate::cmd::links::LinkMode;
+use crate::error::OperationError;
+use crate::file::string_utils::shell_quote;
+use std::env;
+use std::error::Error;
+
+/// Earliest timestamp representable in a ZIP (DOS) header: 1980-01-01 00:00:00 UTC.
+const ZIP_MIN_EPOCH: u64 = 315_532_800;
+const NORMALISED_MODE: &str = "u+rwX,go+rX,go-w";
+
+/// Timestamp stored for every entry: `SOURCE_DATE_EPOCH` when set, otherwise the earliest ZIP timestamp.
+pub fn source_date_epoch() -> Result<u64, Box<dyn Error>> {
+    parse_source_date_epoch(env::var("SOURCE_DATE_EPOCH").ok())
+}
+
+fn parse_source_date_epoch(value: Option<String>) -> Result<u64, Box<dyn Error>> {
+    match value {
+        None => Ok(ZIP_MIN_EPOCH),
+        Some(value) => match value.trim().parse::<u64>() {
+            Ok(epoch) => Ok(epoch),
+            Err(_) => Err(OperationError::InvalidSourceDateEpoch(value).into()),
+        },
+    }
+}
+
+/// GNU tar command producing byte-for-byte identical archives for identical input trees.
+/// `link_flags` come from `links::tar_create_flags`.
+pub fn tar_cmd(archive: &str, files: &str, epoch: u64, link_flags: &str) -> String {
+    let archive = shell_quote(archive);
+    format!(
+        "LC_ALL=C tar --sort=name --format=gnu --mtime=@{epoch} --owner=0 --group=0 --numeric-owner \
+        --mode='{NORMALISED_MODE}'{link_flags} -cf {archive} {files}"
+    )
+}
+
+/// Copies the input into a staging directory where timestamps and permissions are normalised,
+/// then zips the sorted file list without extra attributes (uid/gid, extended timestamps).
+/// The copy goes through `tar`, which strips leading `/` and `../` like it does in archives,
+/// so nothing is written outside the staging directory. The tar stream is written to a file first,
+/// so a missing or unreadable input fails the command instead of being left out. Runs in a subshell,
+/// so it can be chained with further steps.
+pub fn zip_cmd(archive: &str, files: &str, epoch: u64, links: Option<LinkMode>) -> String {
+    let archive = shell_quote(archive);
+    let epoch = epoch.max(ZIP_MIN_EPOCH);
+    // the staging copy keeps links as links
+    let (find_filter, zip_flags) = match links {
+        Some(LinkMode::Preserve) => ("", " -y"),
+        Some(LinkMode::Skip) => (" ! -type l", ""),
+        _ => ("", ""),
+    };
+    format!(
+        "(tmp= && stream= && out=$(realpath -m {archive}) && tmp=$(mktemp -d) && stream=$(mktemp) \
+        && tar -cf \"$stream\" {files} && tar -xf \"$stream\" -C \"$tmp\" && rm -f \"$stream\" \
+        && chmod -R '{NORMALISED_MODE}' \"$tmp\" && find \"$tmp\" -exec touch -h -d @{epoch} {{}} + \
+        && (cd \"$tmp\" && find . -mindepth 1{find_filter} | LC_ALL=C sort | TZ=UTC zip -X -D -6{zip_flags} -@ \"$out\"); \
+        status=$?; rm -rf \"$tmp\" \"$stream\"; exit $status)"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::command_runner::{execute_cmd, execute_cmd_get_lines};
+    use crate::file::temp_dir::TempDir;
+    use std::fs;
+
+    #[test]
+    fn should_default_source_date_epoch() {
+        assert_eq!(parse_source_date_epoch(None).unwrap(), ZIP_MIN_EPOCH);
+    }
+
+    #[test]
+    fn should_parse_source_date_epoch() {
+        let epoch = parse_source_date_epoch(Some("1700000000".to_owned())).unwrap();
+        assert_eq!(epoch, 1_700_000_000);
+        assert!(parse_source_date_epoch(Some("yesterday".to_owned())).is_err());
+    }
+
+    /// Builds the archive of `../in/*` from the `work` directory, with the input files touched first.
+    fn build_archive(dir: &TempDir, name: &str, touched_at: &str) -> Vec<u8> {
+        let work = shell_quote(&dir.path().join("work").display().to_string());
+        execute_cmd(&format!(
+            "cd {work} && touch -d @{touched_at} ../in/a.txt ../in/sub ../in/sub/b.txt"
+        ))
+        .unwrap();
+        let cmd = match name.ends_with(".zip") {
+            true => zip_cmd(name, "../in/*", ZIP_MIN_EPOCH, None),
+            false => tar_cmd(name, "../in/*", ZIP_MIN_EPOCH, ""),
+        };
+        execute_cmd(&format!("cd {work} && {cmd}")).unwrap();
+        let archive = dir.path().join("work").join(name);
+        let bytes = fs::read(&archive).unwrap();
+        fs::remove_file(&archive).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn should_build_identical_archives() {
+        let dir = TempDir::new("reproducible_test").unwrap();
+        fs::create_dir_all(dir.path().join("in/sub")).unwrap();
+        fs::create_dir_all(dir.path().join("work")).unwrap();
+        fs::write(dir.path().join("in/a.txt"), "a").unwrap();
+        fs::write(dir.path().join("in/sub/b.txt"), "b").unwrap();
+
+        for name in ["a.zip", "a.tar"] {
+            let first = build_archive(&dir, name, "1600000000");
+            let second = build_archive(&dir, name, "1700000000");
+            assert!(!first.is_empty());
+            assert_eq!(first, second, "{name} differs");
+        }
+
+        // `..` components are stripped, the staging copy stays inside its directory
+        let work = shell_quote(&dir.path().join("work").display().to_string());
+        execute_cmd(&format!(
+            "cd {work} && {}",
+            zip_cmd("a.zip", "../in/*", ZIP_MIN_EPOCH, None)
+        ))
+        .unwrap();
+        let names = execute_cmd_get_lines(&format!("cd {work} && zipinfo -1 a.zip"));
+        assert_eq!(names, vec!["in/a.txt", "in/sub/b.txt"]);
+
+        // nothing is left out silently
+        let missing = zip_cmd(
+            "b.zip",
+            "../in/a.txt ../in/missing.txt",
+            ZIP_MIN_EPOCH,
+            None,
+        );
+        assert!(execute_cmd(&format!("cd {work} && {missing}")).is_err());
+        assert!(!dir.path().join("work/b.zip").exists());
+    }
+}
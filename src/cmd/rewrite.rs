@@ -0,0 +1,256 @@
+use crate::archive::archive_entry::{ArchiveEntry, ArchiveEntryKind};
+use crate::archive::{path_safety, unpacker};
+use crate::cmd::command_runner::execute_cmd_get_lines;
+use crate::error::OperationError;
+use crate::file::list_file;
+use crate::file::list_file::ListFile;
+use crate::file::string_utils::shell_quote;
+use std::collections::HashMap;
+use std::error::Error;
+
+/// How entry paths are changed on extraction. Stripping happens first, the rules are applied
+/// to what is left, in order.
+#[derive(Debug, Clone, Default)]
+pub struct PathRewrite {
+    pub strip_components: usize,
+    /// `sed -E` substitutions, e.g. `s/^docs/manual/` or `s|\.yml$|.yaml|`.
+    pub rules: Vec<String>,
+}
+
+impl PathRewrite {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.strip_components == 0 && self.rules.is_empty()
+    }
+
+    /// New relative path of every entry, by its stored path. Entries with nothing left after
+    /// stripping, or rewritten to a path outside the destination, are left out.
+    pub fn rewrite_paths(
+        &self,
+        entries: &[ArchiveEntry],
+    ) -> Result<HashMap<String, String>, Box<dyn Error>> {
+        let stripped = entries
+            .iter()
+            .filter_map(|entry| {
+                let path = path_safety::normalize(&entry.path).ok()?;
+                let stripped = path
+                    .split('/')
+                    .skip(self.strip_components)
+                    .collect::<Vec<&str>>()
+                    .join("/");
+                (!stripped.is_empty()).then(|| (entry.path.to_owned(), stripped))
+            })
+            .collect::<Vec<(String, String)>>();
+        let rewritten_paths = match self.rules.is_empty() || stripped.is_empty() {
+            true => stripped,
+            false => self.apply_rules(stripped)?,
+        };
+        check_collisions(entries, &rewritten_paths)?;
+        Ok(rewritten_paths.into_iter().collect())
+    }
+
+    /// Runs the stripped paths through `sed`, all of them at once.
+    fn apply_rules(
+        &self,
+        stripped: Vec<(String, String)>,
+    ) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+        let paths = ListFile::lines(
+            &stripped
+                .iter()
+                .map(|(_, path)| path.to_owned())
+                .collect::<Vec<String>>(),
+        )?;
+        let rules = self
+            .rules
+            .iter()
+            .map(|rule| format!(" -e {}", shell_quote(rule)))
+            .collect::<String>();
+        let rewritten = execute_cmd_get_lines(&format!("sed -E{rules} {}", paths.path()));
+        if rewritten.len() != stripped.len() {
+            return Err(OperationError::InvalidArgument(format!(
+                "--rewrite {}",
+                self.rules.join(" ")
+            ))
+            .into());
+        }
+        Ok(stripped
+            .into_iter()
+            .zip(rewritten)
+            .filter_map(|((stored, _), path)| {
+                let path = path_safety::normalize(&path).ok()?;
+                (!path.is_empty()).then_some((stored, path))
+            })
+            .collect())
+    }
+}
+
+/// Two entries rewritten to the same path would overwrite each other; directories may be merged.
+fn check_collisions(
+    entries: &[ArchiveEntry],
+    rewritten_paths: &[(String, String)],
+) -> Result<(), Box<dyn Error>> {
+    let kinds = entries
+        .iter()
+        .map(|entry| (entry.path.as_str(), entry.kind))
+        .collect::<HashMap<&str, ArchiveEntryKind>>();
+    let mut by_destination: HashMap<&str, &str> = HashMap::new();
+    for (stored, destination) in rewritten_paths {
+        match by_destination.insert(destination, stored) {
+            Some(other)
+                if kinds.get(other) != Some(&ArchiveEntryKind::Directory)
+                    || kinds.get(stored.as_str()) != Some(&ArchiveEntryKind::Directory) =>
+            {
+                return Err(OperationError::RewriteCollision(
+                    other.to_owned(),
+                    stored.to_owned(),
+                    destination.to_owned(),
+                )
+                .into());
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Accepts `sed` substitutions only, e.g. `s/^docs/manual/`, with any delimiter. Only the `g`, `I`
+/// and occurrence number flags are allowed - `e` runs the result as a command, `w` writes a file.
+pub fn parse_rule(value: &str) -> Result<String, Box<dyn Error>> {
+    let mut chars = value.chars();
+    let is_substitution = match (chars.next(), chars.next()) {
+        (Some('s'), Some(delimiter))
+            if !delimiter.is_alphanumeric() && delimiter != '\\' && !value.contains('\n') =>
+        {
+            substitution_flags(chars.as_str(), delimiter).is_some_and(|flags| {
+                flags
+                    .chars()
+                    .all(|flag| flag == 'g' || flag == 'I' || flag.is_ascii_digit())
+            })
+        }
+        _ => false,
+    };
+    match is_substitution {
+        true => Ok(value.to_owned()),
+        false => Err(OperationError::InvalidArgument(format!("--rewrite {value}")).into()),
+    }
+}
+
+/// What follows the pattern and the replacement, which end with unescaped delimiters.
+fn substitution_flags(rest: &str, delimiter: char) -> Option<&str> {
+    let mut delimiters = 0;
+    let mut is_escaped = false;
+    for (idx, c) in rest.char_indices() {
+        match c {
+            _ if is_escaped => is_escaped = false,
+            '\\' => is_escaped = true,
+            _ if c == delimiter => {
+                delimiters += 1;
+                if delimiters == 2 {
+                    return Some(&rest[idx + c.len_utf8()..]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Extracts the entries into a hidden staging directory inside `extract_dir` (a shell word), removed
+/// however the command ends, and moves every entry to its destination, relative to `extract_dir`;
+/// directories are only created. `tar_flags` are added when the archive is extracted with `tar`.
+pub fn extract_cmd(
+    archive: &str,
+    tar_flags: &str,
+    moves: &[(ArchiveEntry, String)],
+    extract_dir: &str,
+) -> String {
+    if moves.is_empty() {
+        return "true".to_owned();
+    }
+    let (dirs, files): (Vec<_>, Vec<_>) = moves
+        .iter()
+        .partition(|(entry, _)| entry.kind == ArchiveEntryKind::Directory);
+    let dirs = dirs
+        .into_iter()
+        .map(|(_, destination)| destination.to_owned())
+        .collect::<Vec<String>>();
+    let members = files
+        .iter()
+        .map(|(entry, _)| entry.path.to_owned())
+        .collect::<Vec<String>>();
+    let files = files
+        .into_iter()
+        .map(|(entry, destination)| (entry.path.to_owned(), destination.to_owned()))
+        .collect::<Vec<(String, String)>>();
+    let member_list = list_file::pending(&unpacker::member_names(archive, &members));
+    format!(
+        "(rewrite_staging= && trap 'rm -rf \"$rewrite_staging\"' EXIT && trap 'exit 130' INT TERM \
+        && rewrite_staging=$(mktemp -d {extract_dir}/'.staging.XXXXXX') && {} && {} && {})",
+        unpacker::extract_members_cmd(archive, "\"$rewrite_staging\"", &member_list, tar_flags),
+        list_file::mkdirs_cmd(extract_dir, &dirs),
+        list_file::moves_cmd("\"$rewrite_staging\"", &files)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::archive_entry::test_entry;
+
+    fn entry(path: &str) -> ArchiveEntry {
+        test_entry(path, ArchiveEntryKind::File)
+    }
+
+    #[test]
+    fn should_parse_rules() {
+        assert!(parse_rule("s/^docs/manual/").is_ok());
+        assert!(parse_rule("s|\\.yml$|.yaml|g").is_ok());
+        assert!(parse_rule("s/docs").is_err());
+        assert!(parse_rule("y/abc/xyz/").is_err());
+        assert!(parse_rule("s/a\\/b/c/2I").is_ok());
+        assert!(parse_rule("s/a/b/e").is_err());
+        assert!(parse_rule("s/a/b/w out.txt").is_err());
+        assert!(parse_rule("s/a/b/;e id").is_err());
+        assert!(parse_rule("s/a/b\\/").is_err());
+    }
+
+    #[test]
+    fn should_rewrite_paths() {
+        let entries = vec![
+            entry("./resources/test/files/test1.txt"),
+            entry("./resources/test/docs/app.yml"),
+            entry("./resources/"),
+        ];
+        let rewrite = PathRewrite {
+            strip_components: 2,
+            rules: vec!["s/^docs/manual/".to_owned(), "s/\\.yml$/.yaml/".to_owned()],
+        };
+        let paths = rewrite.rewrite_paths(&entries).unwrap();
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths["./resources/test/files/test1.txt"], "files/test1.txt");
+        assert_eq!(paths["./resources/test/docs/app.yml"], "manual/app.yaml");
+
+        let escaping = PathRewrite {
+            strip_components: 0,
+            rules: vec!["s|^|../|".to_owned()],
+        };
+        assert!(escaping.rewrite_paths(&entries).unwrap().is_empty());
+
+        let entries = vec![
+            test_entry("a/conf/", ArchiveEntryKind::Directory),
+            test_entry("b/conf/", ArchiveEntryKind::Directory),
+            entry("a/conf/app.yml"),
+            entry("b/conf/app.yml"),
+        ];
+        let merging = PathRewrite {
+            strip_components: 1,
+            rules: Vec::new(),
+        };
+        let error = merging
+            .rewrite_paths(&entries[..2])
+            .map(|paths| paths.len());
+        assert_eq!(error.unwrap(), 2);
+        let error = merging.rewrite_paths(&entries).unwrap_err();
+        assert!(error.to_string().contains("'conf/app.yml'"));
+    }
+}
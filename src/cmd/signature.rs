@@ -0,0 +1,92 @@
+use crate::error::OperationError;
+use crate::file::string_utils::shell_quote;
+use std::error::Error;
+use std::path::Path;
+
+const SIGNATURE_EXTENSION: &str = ".sig";
+
+#[inline]
+pub fn signature_path_for(archive: &str) -> String {
+    format!("{archive}{SIGNATURE_EXTENSION}")
+}
+
+/// Generates an Ed25519 key pair: `{name}.pem` (private, readable only by the owner) and `{name}.pub.pem`.
+pub fn generate_keys_cmd(name: &str) -> Result<String, Box<dyn Error>> {
+    let private_key = format!("{name}.pem");
+    if Path::new(&private_key).exists() {
+        return Err(OperationError::KeyAlreadyExists(private_key).into());
+    }
+    let public_key = shell_quote(&format!("{name}.pub.pem"));
+    let private_key = shell_quote(&private_key);
+    Ok(format!(
+        "openssl genpkey -algorithm ed25519 -out {private_key} && chmod 600 {private_key} \
+        && openssl pkey -in {private_key} -pubout -out {public_key}"
+    ))
+}
+
+/// Writes a detached signature of the archive to `{archive}.sig`.
+pub fn sign_cmd(archive: &str, private_key: &str) -> String {
+    let signature = shell_quote(&signature_path_for(archive));
+    let archive = shell_quote(archive);
+    let private_key = shell_quote(private_key);
+    format!("openssl pkeyutl -sign -inkey {private_key} -rawin -in {archive} -out {signature}")
+}
+
+/// Fails when the archive has no detached signature, otherwise returns a command failing on signature mismatch.
+pub fn verify_cmd(archive: &str, public_key: &str) -> Result<String, Box<dyn Error>> {
+    ensure_key_exists(public_key)?;
+    let signature = signature_path_for(archive);
+    if !Path::new(&signature).is_file() {
+        return Err(OperationError::UnsignedArchive(archive.to_owned()).into());
+    }
+    let signature = shell_quote(&signature);
+    let archive = shell_quote(archive);
+    let public_key = shell_quote(public_key);
+    Ok(format!(
+        "openssl pkeyutl -verify -pubin -inkey {public_key} -rawin -in {archive} -sigfile {signature}"
+    ))
+}
+
+pub fn ensure_key_exists(key: &str) -> Result<(), Box<dyn Error>> {
+    match Path::new(key).is_file() {
+        true => Ok(()),
+        false => Err(OperationError::KeyDoesNotExist(key.to_owned()).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::command_runner::execute_cmd;
+    use crate::file::temp_dir::TempDir;
+    use std::fs;
+    use std::io::Write;
+
+    #[test]
+    fn should_verify_signed_archive() {
+        let dir = TempDir::new("signature_test").unwrap();
+        let key = dir.path().join("release").display().to_string();
+        let other_key = dir.path().join("other").display().to_string();
+        execute_cmd(&generate_keys_cmd(&key).unwrap()).unwrap();
+        execute_cmd(&generate_keys_cmd(&other_key).unwrap()).unwrap();
+        assert!(generate_keys_cmd(&key).is_err());
+
+        let archive = dir.path().join("release.tar").display().to_string();
+        fs::copy("./resources/test/archives/resources_archive.tar", &archive).unwrap();
+        assert!(verify_cmd(&archive, &format!("{key}.pub.pem")).is_err());
+
+        execute_cmd(&sign_cmd(&archive, &format!("{key}.pem"))).unwrap();
+        execute_cmd(&verify_cmd(&archive, &format!("{key}.pub.pem")).unwrap()).unwrap();
+        assert!(
+            execute_cmd(&verify_cmd(&archive, &format!("{other_key}.pub.pem")).unwrap()).is_err()
+        );
+
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&archive)
+            .unwrap()
+            .write_all(b"tampered")
+            .unwrap();
+        assert!(execute_cmd(&verify_cmd(&archive, &format!("{key}.pub.pem")).unwrap()).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+use crate::archive::path_safety;
+use crate::error::OperationError;
+use crate::file::file_metadata::FileMetadata;
+use crate::file::string_utils::shell_quote;
+use std::error::Error;
+use std::fs;
+
+/// Where the paths stored in a new archive start from.
+#[derive(Debug, Clone, Default)]
+pub struct StoredRoot {
+    /// Entries are stored relative to this directory instead of the working directory.
+    pub base_dir: Option<String>,
+    /// Top-level folder every entry is placed in, e.g. `project-1.2`.
+    pub prefix: Option<String>,
+}
+
+impl StoredRoot {
+    #[inline]
+    pub fn is_default(&self) -> bool {
+        self.base_dir.is_none() && self.prefix.is_none()
+    }
+}
+
+pub fn parse_prefix(value: &str) -> Result<String, Box<dyn Error>> {
+    match path_safety::normalize(value) {
+        Ok(prefix) if !prefix.is_empty() => Ok(prefix),
+        _ => Err(OperationError::InvalidArgument(format!("--prefix {value}")).into()),
+    }
+}
+
+/// Files to archive as the archiving tool sees them, from the directory it runs in.
+#[derive(Debug)]
+pub struct StoredFiles {
+    /// Files argument of the tool, e.g. `project-1.2/files/*` for `./resources/test/files/*`.
+    pub files: String,
+    original_path: String,
+    stored_path: String,
+    /// Absolute directory the tool runs in, `None` for the working directory.
+    run_dir: Option<String>,
+    prefix: Option<String>,
+}
+
+impl StoredFiles {
+    pub fn new(metadata: &FileMetadata, root: &StoredRoot) -> Result<StoredFiles, Box<dyn Error>> {
+        let original_path = metadata.path.to_owned();
+        let suffix = metadata.to_string_path()[original_path.len()..].to_owned();
+        let (run_dir, relative_path) = match &root.base_dir {
+            None if root.prefix.is_none() => (None, original_path.to_owned()),
+            None => (None, relative_to(&original_path, ".")?),
+            Some(base_dir) => (
+                Some(fs::canonicalize(base_dir)?.display().to_string()),
+                relative_to(&original_path, base_dir)?,
+            ),
+        };
+        let stored_path = match &root.prefix {
+            Some(prefix) if relative_path == "." => prefix.to_owned(),
+            Some(prefix) => format!("{prefix}/{relative_path}"),
+            None => relative_path,
+        };
+        Ok(StoredFiles {
+            files: format!("{stored_path}{suffix}"),
+            original_path,
+            stored_path,
+            run_dir,
+            prefix: root.prefix.to_owned(),
+        })
+    }
+
+    /// Path under the archived one as the tool sees it, e.g. a symlink found while scanning.
+    pub fn rebase(&self, path: &str) -> String {
+        match path.strip_prefix(&self.original_path) {
+            Some(rest) => format!("{}{rest}", self.stored_path),
+            None => path.to_owned(),
+        }
+    }
+
+    /// Runs the command in the directory the stored paths start from. A prefix is a symlink
+    /// in a temporary directory, pointing to the base directory.
+    pub fn wrap_cmd(&self, cmd: &str) -> String {
+        match (&self.run_dir, &self.prefix) {
+            (None, None) => cmd.to_owned(),
+            (Some(run_dir), None) => format!("(cd {} && {cmd})", shell_quote(run_dir)),
+            (run_dir, Some(prefix)) => {
+                let target = match run_dir {
+                    Some(run_dir) => shell_quote(run_dir),
+                    None => "\"$PWD\"".to_owned(),
+                };
+                let link = format!("\"$staging\"/{}", shell_quote(prefix));
+                format!(
+                    "(staging=$(mktemp -d) && mkdir -p \"$(dirname {link})\" && ln -s {target} {link} \
+                    && cd \"$staging\" && {cmd}; status=$?; rm -rf \"$staging\"; exit $status)"
+                )
+            }
+        }
+    }
+}
+
+/// `path` relative to `base_dir`, `.` for the directory itself.
+fn relative_to(path: &str, base_dir: &str) -> Result<String, Box<dyn Error>> {
+    let canonical_path = fs::canonicalize(path)?;
+    let canonical_base = fs::canonicalize(base_dir)?;
+    match canonical_path.strip_prefix(&canonical_base) {
+        Ok(relative) if relative.as_os_str().is_empty() => Ok(".".to_owned()),
+        Ok(relative) => Ok(relative.display().to_string()),
+        Err(_) => {
+            Err(OperationError::OutsideBaseDirectory(path.to_owned(), base_dir.to_owned()).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files_metadata() -> FileMetadata {
+        FileMetadata {
+            path: "./resources/test/files".to_owned(),
+            wildcard: None,
+            is_directory: true,
+        }
+    }
+
+    #[test]
+    fn should_store_relative_to_base_dir() {
+        let root = StoredRoot {
+            base_dir: Some("./resources/test".to_owned()),
+            prefix: None,
+        };
+        let stored = StoredFiles::new(&files_metadata(), &root).unwrap();
+        assert_eq!(stored.files, "files/*");
+        assert_eq!(stored.rebase("./resources/test/files/link"), "files/link");
+        assert!(
+            stored
+                .wrap_cmd("tar -cf '/a.tar' files/*")
+                .starts_with("(cd '/")
+        );
+
+        let outside = StoredRoot {
+            base_dir: Some("./src".to_owned()),
+            prefix: None,
+        };
+        assert!(StoredFiles::new(&files_metadata(), &outside).is_err());
+    }
+
+    #[test]
+    fn should_store_under_prefix() {
+        let root = StoredRoot {
+            base_dir: Some("./resources/test/files".to_owned()),
+            prefix: Some(parse_prefix("project-1.2").unwrap()),
+        };
+        let stored = StoredFiles::new(&files_metadata(), &root).unwrap();
+        assert_eq!(stored.files, "project-1.2/*");
+        assert!(
+            stored
+                .wrap_cmd("zip -r '/a.zip' project-1.2/*")
+                .contains("ln -s '/")
+        );
+        assert!(parse_prefix("../up").is_err());
+    }
+}
@@ -30,4 +30,125 @@ pub enum OperationError {
         "Wildcard is only supported at the end of a path - e.g. './my/path/file*' or './my/path/.*txt'"
     )]
     InvalidWildcardIndex,
+
+    #[error("Option '{0}' requires a value.")]
+    MissingOptionValue(String),
+
+    #[error("Manifest file is required when verifying a directory - use '--manifest-file'.")]
+    ManifestNotProvided,
+
+    #[error("Manifest '{0}' does not exist.")]
+    ManifestDoesNotExist(String),
+
+    #[error("Key '{0}' does not exist.")]
+    KeyDoesNotExist(String),
+
+    #[error("Key '{0}' already exists.")]
+    KeyAlreadyExists(String),
+
+    #[error("Archive '{0}' is not signed - signature file is missing.")]
+    UnsignedArchive(String),
+
+    #[error("Public key is required to verify the signature - use '--verify-key'.")]
+    PublicKeyNotProvided,
+
+    #[error("Invalid SOURCE_DATE_EPOCH '{0}'. Expected number of seconds since 1970-01-01.")]
+    InvalidSourceDateEpoch(String),
+
+    #[error("Deterministic archives can not be encrypted - encryption uses random salt.")]
+    DeterministicEncryption,
+
+    #[error("Missing {0} argument.")]
+    MissingOperand(String),
+
+    #[error("'{0}' is not a directory.")]
+    NotADirectory(String),
+
+    #[error("Could not read entries of the archive '{0}'.")]
+    CouldNotReadArchive(String),
+
+    #[error("Archive '{0}' is corrupted: {1}")]
+    ArchiveIsCorrupted(String, String),
+
+    #[error("No entries of the archive match: {0}.")]
+    NoEntriesMatched(String),
+
+    #[error("Entry '{0}' does not exist in the archive.")]
+    EntryNotFound(String),
+
+    #[error("Entry '{0}' is a directory.")]
+    EntryIsDirectory(String),
+
+    #[error("Invalid regular expression '{0}'.")]
+    InvalidPattern(String),
+
+    #[error("'{0}' is not an archive, so the path can not descend into it.")]
+    NotAnArchive(String),
+
+    #[error("Every entry of '{0}' would be written outside the destination directory.")]
+    UnsafeArchive(String),
+
+    #[error("Extraction limit {0} exceeded: {1}.")]
+    ExtractionLimitExceeded(String, String),
+
+    #[error("'{0}' is not inside the base directory '{1}'.")]
+    OutsideBaseDirectory(String, String),
+
+    #[error(
+        "Not enough free space in '{0}': {1} bytes needed, {2} bytes available - use '--force' to continue anyway."
+    )]
+    InsufficientDiskSpace(String, u64, u64),
+
+    #[error("Can not read '{0}' archives: {1}.")]
+    UnsupportedFormat(String, String),
+
+    #[error("Option '{0}' can not be used with '{1}'.")]
+    UnsupportedOption(String, String),
+
+    #[error("Entries '{0}' and '{1}' would both be extracted to '{2}'.")]
+    RewriteCollision(String, String, String),
+}
+
+impl OperationError {
+    /// Stable identifier of the error, printed in JSON output.
+    pub fn code(&self) -> &'static str {
+        match self {
+            OperationError::InvalidCommand => "INVALID_COMMAND",
+            OperationError::FileDoesNotExist => "FILE_DOES_NOT_EXIST",
+            OperationError::CouldNotCheckFile(_) => "COULD_NOT_CHECK_FILE",
+            OperationError::CouldNotFindDirForFileWithWildcard(_) => {
+                "COULD_NOT_FIND_DIR_FOR_FILE_WITH_WILDCARD"
+            }
+            OperationError::FailedToRunCommand(_) => "FAILED_TO_RUN_COMMAND",
+            OperationError::ArchiveIsDirectory => "ARCHIVE_IS_DIRECTORY",
+            OperationError::InvalidArgument(_) => "INVALID_ARGUMENT",
+            OperationError::CouldNotSpitPath(_) => "COULD_NOT_SPIT_PATH",
+            OperationError::InvalidWildcardIndex => "INVALID_WILDCARD_INDEX",
+            OperationError::MissingOptionValue(_) => "MISSING_OPTION_VALUE",
+            OperationError::ManifestNotProvided => "MANIFEST_NOT_PROVIDED",
+            OperationError::ManifestDoesNotExist(_) => "MANIFEST_DOES_NOT_EXIST",
+            OperationError::KeyDoesNotExist(_) => "KEY_DOES_NOT_EXIST",
+            OperationError::KeyAlreadyExists(_) => "KEY_ALREADY_EXISTS",
+            OperationError::UnsignedArchive(_) => "UNSIGNED_ARCHIVE",
+            OperationError::PublicKeyNotProvided => "PUBLIC_KEY_NOT_PROVIDED",
+            OperationError::InvalidSourceDateEpoch(_) => "INVALID_SOURCE_DATE_EPOCH",
+            OperationError::DeterministicEncryption => "DETERMINISTIC_ENCRYPTION",
+            OperationError::MissingOperand(_) => "MISSING_OPERAND",
+            OperationError::NotADirectory(_) => "NOT_A_DIRECTORY",
+            OperationError::CouldNotReadArchive(_) => "COULD_NOT_READ_ARCHIVE",
+            OperationError::ArchiveIsCorrupted(_, _) => "ARCHIVE_IS_CORRUPTED",
+            OperationError::NoEntriesMatched(_) => "NO_ENTRIES_MATCHED",
+            OperationError::EntryNotFound(_) => "ENTRY_NOT_FOUND",
+            OperationError::EntryIsDirectory(_) => "ENTRY_IS_DIRECTORY",
+            OperationError::InvalidPattern(_) => "INVALID_PATTERN",
+            OperationError::NotAnArchive(_) => "NOT_AN_ARCHIVE",
+            OperationError::UnsafeArchive(_) => "UNSAFE_ARCHIVE",
+            OperationError::ExtractionLimitExceeded(_, _) => "EXTRACTION_LIMIT_EXCEEDED",
+            OperationError::OutsideBaseDirectory(_, _) => "OUTSIDE_BASE_DIRECTORY",
+            OperationError::InsufficientDiskSpace(_, _, _) => "INSUFFICIENT_DISK_SPACE",
+            OperationError::UnsupportedFormat(_, _) => "UNSUPPORTED_FORMAT",
+            OperationError::UnsupportedOption(_, _) => "UNSUPPORTED_OPTION",
+            OperationError::RewriteCollision(_, _, _) => "REWRITE_COLLISION",
+        }
+    }
 }
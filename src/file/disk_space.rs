@@ -0,0 +1,79 @@
+use crate::cmd::command_runner::execute_cmd_get_lines;
+use crate::error::OperationError;
+use crate::file::string_utils::shell_quote;
+use crate::output;
+use std::error::Error;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+/// Free bytes on the filesystem of the directory, or of its closest existing parent.
+pub fn available_bytes(dir: &str) -> Result<u64, Box<dyn Error>> {
+    let existing = Path::new(dir)
+        .ancestors()
+        .find(|path| path.exists())
+        .unwrap_or(Path::new("."));
+    let lines = execute_cmd_get_lines(&format!(
+        "df -Pk {}",
+        shell_quote(&existing.display().to_string())
+    ));
+    // Filesystem 1024-blocks Used Available Capacity Mounted on
+    let available_kb = lines
+        .get(1)
+        .and_then(|line| line.split_whitespace().nth(3))
+        .and_then(|available| available.parse::<u64>().ok())
+        .ok_or_else(|| OperationError::CouldNotCheckFile(dir.to_owned()))?;
+    Ok(available_kb * 1024)
+}
+
+/// Apparent size of the files, e.g. `./my/dir/*`, as the shell expands them.
+pub fn files_size(files: &str) -> u64 {
+    execute_cmd_get_lines(&format!("du -scb {files} 2>/dev/null | tail -n 1"))
+        .first()
+        .and_then(|line| line.split_whitespace().next())
+        .and_then(|size| size.parse::<u64>().ok())
+        .unwrap_or(0)
+}
+
+/// Whether both directories are on the same filesystem, so their free space is shared.
+pub fn same_filesystem(dir: &str, other: &str) -> bool {
+    match (fs::metadata(dir), fs::metadata(other)) {
+        (Ok(dir), Ok(other)) => dir.dev() == other.dev(),
+        _ => false,
+    }
+}
+
+/// Refuses to go on when `required` bytes do not fit in `dir`, unless forced.
+pub fn check(dir: &str, required: u64, force: bool) -> Result<(), Box<dyn Error>> {
+    let available = available_bytes(dir)?;
+    if required <= available {
+        return Ok(());
+    }
+    match force {
+        true => {
+            output::status(&format!(
+                "Continuing with {required} bytes needed and {available} bytes free in '{dir}'."
+            ));
+            Ok(())
+        }
+        false => {
+            Err(OperationError::InsufficientDiskSpace(dir.to_owned(), required, available).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_check_disk_space() {
+        assert!(available_bytes("./target/missing/dir").unwrap() > 0);
+        assert!(files_size("./resources/test/archives/*") > 0);
+        assert!(check(".", 1, false).is_ok());
+        assert!(check(".", u64::MAX, false).is_err());
+        assert!(check(".", u64::MAX, true).is_ok());
+        assert!(same_filesystem(".", "./src"));
+        assert!(!same_filesystem(".", "./target/missing/dir"));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::error::OperationError;
 use crate::file::string_utils;
+use crate::output;
 use std::error::Error;
 use std::path::Path;
 
@@ -64,7 +65,7 @@ pub fn get_file_metadata(full_path: &str) -> Result<FileMetadata, Box<dyn Error>
                     is_directory: p.is_dir(),
                     wildcard: wildcard_value,
                 };
-                println!("Found file/directory: {metadata:?}.");
+                output::status(&format!("Found file/directory: {metadata:?}."));
                 Ok(metadata)
             }
             false => Err(OperationError::FileDoesNotExist.into()),
@@ -0,0 +1,154 @@
+use crate::file::string_utils::shell_quote;
+use crate::file::temp_dir::TempDir;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{env, fs, io, process};
+
+// Entry names are handed to the tools in files: the command line is limited (`ARG_MAX`, and 128 KiB
+// for the single argument of `sh -c` on Linux), the number of entries in an archive is not.
+// Items are NUL separated, for `tar --null -T` and `xargs -0`.
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static PENDING: RefCell<Vec<(String, Vec<u8>)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// List written right away for a command run immediately, removed when dropped.
+#[derive(Debug)]
+pub struct ListFile {
+    _dir: TempDir,
+    path: String,
+}
+
+impl ListFile {
+    pub fn new(items: &[String]) -> io::Result<ListFile> {
+        Self::write(items, '\0')
+    }
+
+    /// One item per line, for tools which can not read NUL separated input, like `sed`.
+    pub fn lines(items: &[String]) -> io::Result<ListFile> {
+        Self::write(items, '\n')
+    }
+
+    fn write(items: &[String], separator: char) -> io::Result<ListFile> {
+        let dir = TempDir::new("list")?;
+        let path = dir.path().join("items").display().to_string();
+        fs::write(&path, join(items, separator))?;
+        Ok(ListFile { _dir: dir, path })
+    }
+
+    /// Quoted for the shell.
+    #[inline]
+    pub fn path(&self) -> String {
+        shell_quote(&self.path)
+    }
+}
+
+/// Quoted path of a list for a command run later. Nothing is written until `finish`, so building
+/// the command can still fail without leaving files behind.
+pub fn pending(items: &[String]) -> String {
+    let id = COUNTER.fetch_add(1, Ordering::SeqCst);
+    let path = env::temp_dir()
+        .join(format!("zip-tar-cli-list-{}-{id}", process::id()))
+        .display()
+        .to_string();
+    PENDING.with(|pending| {
+        pending
+            .borrow_mut()
+            .push((path.to_owned(), join(items, '\0')))
+    });
+    shell_quote(&path)
+}
+
+/// Writes the pending lists of the complete command, which removes them when it ends or is interrupted.
+/// They are dropped when building the command failed.
+pub fn finish(cmd: Result<String, Box<dyn Error>>) -> Result<String, Box<dyn Error>> {
+    let pending = PENDING.with(|pending| pending.take());
+    let cmd = cmd?;
+    if pending.is_empty() {
+        return Ok(cmd);
+    }
+    for (path, content) in &pending {
+        fs::write(path, content)?;
+    }
+    let paths = pending
+        .iter()
+        .map(|(path, _)| shell_quote(path))
+        .collect::<Vec<String>>()
+        .join(" ");
+    Ok(format!(
+        "(trap {} EXIT && trap 'exit 130' INT TERM && {cmd})",
+        shell_quote(&format!("rm -f {paths}"))
+    ))
+}
+
+/// Moves every `(source, destination)` pair out of `source_dir`, a directory right inside the one
+/// the destinations are relative to (a shell word, e.g. `"$staging"`), creating the missing parent
+/// directories first. Neither has to be known when the command is built.
+pub fn moves_cmd(source_dir: &str, moves: &[(String, String)]) -> String {
+    let parents = moves
+        .iter()
+        .filter_map(|(_, destination)| Path::new(destination).parent())
+        .map(|parent| parent.display().to_string())
+        .filter(|parent| !parent.is_empty())
+        .map(|parent| format!("../{parent}"))
+        .collect::<BTreeSet<String>>();
+    let items = moves
+        .iter()
+        .flat_map(|(source, destination)| [source.to_owned(), format!("../{destination}")])
+        .collect::<Vec<String>>();
+    format!(
+        "{} && (cd {source_dir} && xargs -0 -r -n 2 mv -f -- < {})",
+        mkdirs_cmd(source_dir, &parents.into_iter().collect::<Vec<String>>()),
+        pending(&items)
+    )
+}
+
+/// Creates the directories relative to `dir` (a shell word), including the missing parents.
+#[inline]
+pub fn mkdirs_cmd(dir: &str, dirs: &[String]) -> String {
+    format!("(cd {dir} && xargs -0 -r mkdir -p -- < {})", pending(dirs))
+}
+
+#[inline]
+fn join(items: &[String], separator: char) -> Vec<u8> {
+    items
+        .iter()
+        .flat_map(|item| format!("{item}{separator}").into_bytes())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::command_runner::execute_cmd;
+
+    #[test]
+    fn should_write_lists_of_complete_commands_only() {
+        let dir = TempDir::new("list_file_test").unwrap();
+        fs::create_dir_all(dir.path().join("to/.from/-dir")).unwrap();
+        fs::write(dir.path().join("to/.from/-dir/a b.txt"), "a").unwrap();
+        let target = dir.path().join("to").display().to_string();
+
+        let moves = vec![("-dir/a b.txt".to_owned(), "x/a b.txt".to_owned())];
+        let cmd = format!(
+            "{} && {}",
+            mkdirs_cmd(&shell_quote(&target), &["empty".to_owned()]),
+            moves_cmd(&shell_quote(&format!("{target}/.from")), &moves)
+        );
+        let cmd = finish(Ok(cmd)).unwrap();
+        execute_cmd(&cmd).unwrap();
+        assert!(Path::new(&target).join("x/a b.txt").is_file());
+        assert!(Path::new(&target).join("empty").is_dir());
+        assert!(!cmd.contains("a b.txt"));
+
+        let list = pending(&["a".to_owned()]);
+        assert!(finish(Err("failed".into())).is_err());
+        assert!(!Path::new(list.trim_matches('\'')).exists());
+        assert_eq!(finish(Ok("true".to_owned())).unwrap(), "true");
+    }
+}
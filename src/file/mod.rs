@@ -1,2 +1,6 @@
+pub mod disk_space;
 pub mod file_metadata;
+pub mod list_file;
 pub mod string_utils;
+pub mod temp_dir;
+pub mod tree_snapshot;
@@ -21,6 +21,49 @@ pub fn find_file_extension(path: &str) -> Result<String, &'static str> {
     }
 }
 
+/// Wraps the value in single quotes so it is passed to `sh -c` as a single, literal word.
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Matches `*` (any characters except `/`), `**` (any characters including `/`) and `?` (single character).
+/// Patterns without `/` are matched against the file name only, e.g. `*.txt` matches `conf/notes.txt`.
+pub fn glob_matches(pattern: &str, path: &str) -> bool {
+    let pattern = pattern
+        .trim_start_matches("./")
+        .chars()
+        .collect::<Vec<char>>();
+    let path = path.trim_start_matches("./").trim_end_matches('/');
+    let path = match pattern.contains(&'/') {
+        true => path,
+        false => path.rsplit('/').next().unwrap_or(path),
+    };
+    wildcard_matches(&pattern, &path.chars().collect::<Vec<char>>())
+}
+
+fn wildcard_matches(pattern: &[char], value: &[char]) -> bool {
+    match pattern.first() {
+        None => value.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            let rest = &pattern[2..];
+            // `dir/**/file` matches `dir/file` as well
+            if rest.first() == Some(&'/') && wildcard_matches(&rest[1..], value) {
+                return true;
+            }
+            (0..=value.len()).any(|idx| wildcard_matches(rest, &value[idx..]))
+        }
+        Some('*') => {
+            let segment_len = value.iter().position(|c| *c == '/').unwrap_or(value.len());
+            (0..=segment_len).any(|idx| wildcard_matches(&pattern[1..], &value[idx..]))
+        }
+        Some('?') => match value.first() {
+            Some(c) if *c != '/' => wildcard_matches(&pattern[1..], &value[1..]),
+            _ => false,
+        },
+        Some(c) => value.first() == Some(c) && wildcard_matches(&pattern[1..], &value[1..]),
+    }
+}
+
 #[inline]
 fn last_index_of_char(s: &str, to_find: char) -> Option<usize> {
     s.chars()
@@ -54,6 +97,28 @@ mod tests {
         assert_eq!(ext, ".zip");
     }
 
+    #[test]
+    fn should_quote_for_shell() {
+        assert_eq!(shell_quote("./my dir/file.zip"), "'./my dir/file.zip'");
+        assert_eq!(shell_quote("it's.zip"), "'it'\\''s.zip'");
+    }
+
+    #[test]
+    fn should_match_glob() {
+        assert!(glob_matches("*.txt", "./resources/test/test1.txt"));
+        assert!(glob_matches("test?.png", "resources/test/test4.png"));
+        assert!(glob_matches(
+            "resources/*/test1.txt",
+            "./resources/test/test1.txt"
+        ));
+        assert!(!glob_matches("resources/*.txt", "resources/test/test1.txt"));
+        assert!(glob_matches("resources/**.txt", "resources/test/test1.txt"));
+        assert!(glob_matches("**/conf/app.yml", "backup/2025/conf/app.yml"));
+        assert!(glob_matches("**/conf/app.yml", "conf/app.yml"));
+        assert!(glob_matches("conf/**", "conf/nested/app.yml"));
+        assert!(!glob_matches("*.yml", "conf/app.yaml"));
+    }
+
     #[test]
     fn should_find_last_index_of_char() {
         let index = last_index_of_char("/foo/bar/path/example_file.txt", '/').unwrap();
@@ -0,0 +1,105 @@
+use crate::cmd::command_runner::execute_cmd_get_lines;
+use crate::file::string_utils::shell_quote;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    File,
+    Directory,
+    Symlink,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeEntry {
+    pub kind: EntryKind,
+    pub size: u64,
+    pub mode: u32,
+    pub hash: Option<String>,
+    pub link_target: Option<String>,
+}
+
+/// Every file, directory and symlink below `root`, keyed by its path relative to `root` (e.g. `my/dir/file.txt`).
+/// Regular files are hashed with `sha256sum`.
+pub fn snapshot(root: &Path) -> Result<BTreeMap<String, TreeEntry>, Box<dyn Error>> {
+    let mut entries = BTreeMap::new();
+    collect(root, "", &mut entries)?;
+
+    let hashes = hash_files(root);
+    for (path, entry) in entries.iter_mut() {
+        if entry.kind == EntryKind::File {
+            entry.hash = hashes.get(path).cloned();
+        }
+    }
+    Ok(entries)
+}
+
+fn collect(
+    dir: &Path,
+    prefix: &str,
+    entries: &mut BTreeMap<String, TreeEntry>,
+) -> Result<(), Box<dyn Error>> {
+    for dir_entry in fs::read_dir(dir)? {
+        let dir_entry = dir_entry?;
+        let name = dir_entry.file_name().to_string_lossy().to_string();
+        let relative_path = format!("{prefix}{name}");
+        let metadata = fs::symlink_metadata(dir_entry.path())?;
+
+        let kind = match metadata.file_type() {
+            t if t.is_symlink() => EntryKind::Symlink,
+            t if t.is_dir() => EntryKind::Directory,
+            _ => EntryKind::File,
+        };
+        let link_target = match kind {
+            EntryKind::Symlink => Some(fs::read_link(dir_entry.path())?.display().to_string()),
+            _ => None,
+        };
+        entries.insert(
+            relative_path.to_owned(),
+            TreeEntry {
+                kind,
+                size: metadata.len(),
+                mode: metadata.permissions().mode() & 0o7777,
+                hash: None,
+                link_target,
+            },
+        );
+        if kind == EntryKind::Directory {
+            collect(&dir_entry.path(), &format!("{relative_path}/"), entries)?;
+        }
+    }
+    Ok(())
+}
+
+fn hash_files(root: &Path) -> BTreeMap<String, String> {
+    let root = shell_quote(&root.display().to_string());
+    execute_cmd_get_lines(&format!(
+        "cd {root} && find . -type f -exec sha256sum {{}} +"
+    ))
+    .iter()
+    .filter_map(|line| line.split_once("  "))
+    .map(|(hash, path)| (path.trim_start_matches("./").to_owned(), hash.to_owned()))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_snapshot_directory() {
+        let entries = snapshot(Path::new("./resources/test")).unwrap();
+        assert_eq!(entries["files"].kind, EntryKind::Directory);
+
+        let file = &entries["files/test1.txt"];
+        assert_eq!(file.kind, EntryKind::File);
+        assert_eq!(file.size, 0);
+        assert_eq!(
+            file.hash.as_deref(),
+            Some("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+        );
+    }
+}
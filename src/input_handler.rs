@@ -12,6 +12,16 @@ fn read_string() -> String {
     input.trim().to_owned()
 }
 
+/// Prints the prompt and returns the answer, or `None` when the user left it empty.
+pub fn read_optional(prompt: &str) -> Option<String> {
+    println!("{prompt}");
+    let input = read_string();
+    match input.is_empty() {
+        true => None,
+        false => Some(input),
+    }
+}
+
 #[inline]
 pub fn read_int() -> Result<i32, ParseIntError> {
     let input = read_string();
@@ -1,12 +1,17 @@
-use crate::cmd::{arg_parser, command_builder, command_runner};
+use crate::archive::list;
+use crate::cmd::{arg_parser, command_builder, command_runner, manifest};
 use crate::error::OperationError;
+use crate::tui::browser;
 use std::env;
 use std::process::exit;
 
+mod archive;
 mod cmd;
 mod error;
 mod file;
 mod input_handler;
+mod output;
+mod tui;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -15,6 +20,10 @@ fn main() {
     if args.len() > 1 {
         match arg_parser::parse_and_run(args) {
             Ok(_) => exit(0),
+            Err(e) if output::is_machine_readable() => {
+                output::print_error(e.as_ref());
+                exit(1)
+            }
             Err(e) => eprintln!("{e}"),
         }
     }
@@ -36,6 +45,9 @@ fn print_menu() {
     \n5. Add to exising archive.\
     \n6. Extract all archives in a specific directory.\
     \n7. Extract all archives in the current directory.\
+    \n8. Verify archive or directory against SHA-256 manifest.\
+    \n9. List archive contents.\
+    \n10. Browse archive interactively.\
     \n0. Exit program.";
     println!("{menu}");
 }
@@ -49,6 +61,9 @@ fn handle_menu_option(option: i32) {
         5 => command_builder::add_to_exising_archive(),
         6 => command_builder::extract_all(),
         7 => command_builder::extract_all_in_current_dir(),
+        8 => manifest::verify(),
+        9 => return handle_operation_result(list::list()),
+        10 => return handle_operation_result(browser::browse()),
         0 => exit(0),
         _ => Err(OperationError::InvalidCommand.into()),
     };
@@ -63,3 +78,10 @@ fn handle_menu_option(option: i32) {
         }
     }
 }
+
+fn handle_operation_result(result: Result<(), Box<dyn std::error::Error>>) {
+    match result {
+        Ok(_) => exit(0),
+        Err(error) => eprintln!("{error}"),
+    }
+}
@@ -0,0 +1,240 @@
+use crate::error::OperationError;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+static STATUS_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Ndjson,
+}
+
+pub fn parse_format(value: &str) -> Result<OutputFormat, Box<dyn Error>> {
+    match value {
+        "text" => Ok(OutputFormat::Text),
+        "json" => Ok(OutputFormat::Json),
+        "ndjson" => Ok(OutputFormat::Ndjson),
+        _ => Err(OperationError::InvalidArgument(format!("--format {value}")).into()),
+    }
+}
+
+/// Set once, before the first message is printed; later calls are ignored.
+pub fn set_format(format: OutputFormat) {
+    let _ = FORMAT.set(format);
+}
+
+#[inline]
+pub fn format() -> OutputFormat {
+    FORMAT.get().copied().unwrap_or_default()
+}
+
+#[inline]
+pub fn is_machine_readable() -> bool {
+    format() != OutputFormat::Text
+}
+
+/// Used by operations writing data to stdout, e.g. printing an archive entry.
+pub fn redirect_status_to_stderr() {
+    STATUS_TO_STDERR.store(true, Ordering::SeqCst);
+}
+
+/// Progress message for humans. Goes to stderr in JSON modes, so stdout carries only structured output.
+pub fn status(message: &str) {
+    match is_machine_readable() || STATUS_TO_STDERR.load(Ordering::SeqCst) {
+        true => eprintln!("{message}"),
+        false => println!("{message}"),
+    }
+}
+
+/// JSON: one document with `items` under `key`. NDJSON: one line per item followed by a summary line.
+pub fn print_result(operation: &str, fields: Vec<(&str, Json)>, key: &str, items: Vec<Json>) {
+    let mut summary = vec![
+        ("operation", Json::from(operation)),
+        ("status", Json::from("ok")),
+    ];
+    summary.extend(fields);
+    match format() {
+        OutputFormat::Ndjson => {
+            for item in items {
+                println!("{item}");
+            }
+            println!("{}", Json::object(summary));
+        }
+        _ => {
+            if !key.is_empty() {
+                summary.push((key, Json::Array(items)));
+            }
+            println!("{}", Json::object(summary));
+        }
+    }
+}
+
+pub fn print_error(error: &(dyn Error + 'static)) {
+    let error_fields = vec![
+        ("code", Json::from(error_code(error))),
+        ("message", Json::from(error.to_string())),
+    ];
+    let document = Json::object(vec![
+        ("status", Json::from("error")),
+        ("error", Json::object(error_fields)),
+    ]);
+    println!("{document}");
+}
+
+fn error_code(error: &(dyn Error + 'static)) -> &'static str {
+    if let Some(error) = error.downcast_ref::<OperationError>() {
+        error.code()
+    } else if error.downcast_ref::<io::Error>().is_some() {
+        "IO_ERROR"
+    } else {
+        "UNKNOWN_ERROR"
+    }
+}
+
+/// Minimal JSON value, enough for the flat records this program prints.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Int(u64),
+    Float(f64),
+    Str(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn object(fields: Vec<(&str, Json)>) -> Json {
+        Json::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.to_owned(), value))
+                .collect(),
+        )
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Json::Str(value.to_owned())
+    }
+}
+
+impl From<String> for Json {
+    fn from(value: String) -> Self {
+        Json::Str(value)
+    }
+}
+
+impl From<u64> for Json {
+    fn from(value: u64) -> Self {
+        Json::Int(value)
+    }
+}
+
+impl From<f64> for Json {
+    fn from(value: f64) -> Self {
+        Json::Float(value)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Self {
+        Json::Bool(value)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(value) => value.into(),
+            None => Json::Null,
+        }
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(value) => write!(f, "{value}"),
+            Json::Int(value) => write!(f, "{value}"),
+            Json::Float(value) if value.is_finite() => write!(f, "{value}"),
+            Json::Float(_) => write!(f, "null"),
+            Json::Str(value) => write_escaped(f, value),
+            Json::Array(values) => {
+                write!(f, "[")?;
+                for (idx, value) in values.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(fields) => {
+                write!(f, "{{")?;
+                for (idx, (key, value)) in fields.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_escaped(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_escaped(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in value.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    write!(f, "\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_serialize_json() {
+        let json = Json::object(vec![
+            ("path", Json::from("dir/\"quoted\"\tfile")),
+            ("size", Json::from(12u64)),
+            ("ratio", Json::from(Some(0.5))),
+            ("target", Json::from(None::<String>)),
+            (
+                "items",
+                Json::Array(vec![Json::from(true), Json::from(false)]),
+            ),
+        ]);
+        assert_eq!(
+            json.to_string(),
+            r#"{"path":"dir/\"quoted\"\tfile","size":12,"ratio":0.5,"target":null,"items":[true,false]}"#
+        );
+    }
+
+    #[test]
+    fn should_find_error_code() {
+        let error: Box<dyn Error> = OperationError::FileDoesNotExist.into();
+        assert_eq!(error_code(error.as_ref()), "FILE_DOES_NOT_EXIST");
+    }
+}
@@ -0,0 +1,169 @@
+use crate::archive::archive_entry::{ArchiveEntry, ArchiveEntryKind};
+use std::collections::{HashMap, HashSet};
+
+pub const ROOT: usize = 0;
+
+#[derive(Debug)]
+pub struct TreeNode {
+    pub name: String,
+    pub path: String,
+    /// Index of the archive entry; `None` for the root and directories the archive has no entry for.
+    pub entry: Option<usize>,
+    pub is_directory: bool,
+    pub children: Vec<usize>,
+}
+
+/// Entries of an archive arranged as a directory tree, node `ROOT` being the archive itself.
+#[derive(Debug)]
+pub struct ArchiveTree {
+    pub nodes: Vec<TreeNode>,
+}
+
+impl ArchiveTree {
+    pub fn build(entries: &[ArchiveEntry]) -> ArchiveTree {
+        let mut tree = ArchiveTree {
+            nodes: vec![TreeNode {
+                name: String::new(),
+                path: String::new(),
+                entry: None,
+                is_directory: true,
+                children: Vec::new(),
+            }],
+        };
+        let mut node_by_path: HashMap<String, usize> = HashMap::new();
+
+        for (idx, entry) in entries.iter().enumerate() {
+            let path = entry.normalized_path().trim_end_matches('/');
+            if path.is_empty() || path == "." {
+                continue;
+            }
+            let mut parent = ROOT;
+            let mut current_path = String::new();
+            let components = path.split('/').collect::<Vec<&str>>();
+            for (component_idx, component) in components.iter().enumerate() {
+                if !current_path.is_empty() {
+                    current_path.push('/');
+                }
+                current_path.push_str(component);
+                let is_last = component_idx == components.len() - 1;
+
+                let node = match node_by_path.get(&current_path) {
+                    Some(node) => *node,
+                    None => {
+                        tree.nodes.push(TreeNode {
+                            name: component.to_string(),
+                            path: current_path.to_owned(),
+                            entry: None,
+                            is_directory: true,
+                            children: Vec::new(),
+                        });
+                        let node = tree.nodes.len() - 1;
+                        tree.nodes[parent].children.push(node);
+                        node_by_path.insert(current_path.to_owned(), node);
+                        node
+                    }
+                };
+                if is_last {
+                    tree.nodes[node].entry = Some(idx);
+                    tree.nodes[node].is_directory = entry.kind == ArchiveEntryKind::Directory;
+                }
+                parent = node;
+            }
+        }
+        tree.sort_children();
+        tree
+    }
+
+    /// Directories first, then by name.
+    fn sort_children(&mut self) {
+        for idx in 0..self.nodes.len() {
+            let mut children = std::mem::take(&mut self.nodes[idx].children);
+            children.sort_by(|a, b| {
+                let (a, b) = (&self.nodes[*a], &self.nodes[*b]);
+                b.is_directory
+                    .cmp(&a.is_directory)
+                    .then(a.name.cmp(&b.name))
+            });
+            self.nodes[idx].children = children;
+        }
+    }
+
+    /// `(depth, node)` pairs of nodes visible when only the `expanded` directories are open.
+    pub fn visible_rows(&self, expanded: &HashSet<usize>) -> Vec<(usize, usize)> {
+        let mut rows = Vec::new();
+        self.collect_rows(ROOT, 0, expanded, &mut rows);
+        rows
+    }
+
+    fn collect_rows(
+        &self,
+        node: usize,
+        depth: usize,
+        expanded: &HashSet<usize>,
+        rows: &mut Vec<(usize, usize)>,
+    ) {
+        for child in &self.nodes[node].children {
+            rows.push((depth, *child));
+            if expanded.contains(child) {
+                self.collect_rows(*child, depth + 1, expanded, rows);
+            }
+        }
+    }
+
+    /// The node itself when it is not a directory, otherwise every non-directory node below it.
+    pub fn files_under(&self, node: usize) -> Vec<usize> {
+        match self.nodes[node].is_directory {
+            false => vec![node],
+            true => self.nodes[node]
+                .children
+                .iter()
+                .flat_map(|child| self.files_under(*child))
+                .collect(),
+        }
+    }
+
+    pub fn parent_of(&self, node: usize) -> Option<usize> {
+        self.nodes
+            .iter()
+            .position(|candidate| candidate.children.contains(&node))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::archive_entry::test_entry;
+
+    #[test]
+    fn should_build_tree_with_implicit_directories() {
+        let entries = vec![
+            test_entry("./", ArchiveEntryKind::Directory),
+            test_entry("./z.txt", ArchiveEntryKind::File),
+            test_entry("./conf/app.yml", ArchiveEntryKind::File),
+            test_entry("./conf/nested/", ArchiveEntryKind::Directory),
+        ];
+        let tree = ArchiveTree::build(&entries);
+
+        let names = |rows: Vec<(usize, usize)>| {
+            rows.iter()
+                .map(|(depth, node)| format!("{depth}:{}", tree.nodes[*node].name))
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(
+            names(tree.visible_rows(&HashSet::new())),
+            vec!["0:conf", "0:z.txt"]
+        );
+
+        let conf = tree.nodes[ROOT].children[0];
+        assert_eq!(tree.nodes[conf].entry, None);
+        assert_eq!(
+            names(tree.visible_rows(&HashSet::from([conf]))),
+            vec!["0:conf", "1:nested", "1:app.yml", "0:z.txt"]
+        );
+
+        let files = tree.files_under(conf);
+        assert_eq!(files.len(), 1);
+        assert_eq!(tree.nodes[files[0]].path, "conf/app.yml");
+        assert_eq!(tree.parent_of(files[0]), Some(conf));
+    }
+}
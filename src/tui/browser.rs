@@ -0,0 +1,299 @@
+use crate::archive::archive_entry::{ArchiveEntry, ArchiveEntryKind};
+use crate::archive::{archive_entry, cat, limits, path_safety, unpacker};
+use crate::cmd::command_builder;
+use crate::cmd::command_runner::execute_cmd_get_lines;
+use crate::error::OperationError;
+use crate::file::file_metadata::FileMetadata;
+use crate::input_handler;
+use crate::tui::archive_tree::{ArchiveTree, ROOT};
+use crate::tui::terminal::{Key, Terminal};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+
+/// Only the beginning of an entry is read for the preview.
+const PREVIEW_BYTES: usize = 64 * 1024;
+const HEADER_LINES: usize = 2;
+const FOOTER_LINES: usize = 4;
+const HELP: &str =
+    "up/down move  right/enter open  left close  space select  p preview  x extract  q quit";
+
+struct Browser {
+    archive: String,
+    entries: Vec<ArchiveEntry>,
+    tree: ArchiveTree,
+    expanded: HashSet<usize>,
+    selected: HashSet<usize>,
+    cursor: usize,
+    scroll: usize,
+    message: String,
+}
+
+pub fn browse() -> Result<(), Box<dyn Error>> {
+    let archive = input_handler::read_path_to_archive()?;
+    browse_archive(&archive)
+}
+
+pub fn browse_path(archive: &FileMetadata) -> Result<(), Box<dyn Error>> {
+    if archive.is_directory {
+        return Err(OperationError::ArchiveIsDirectory.into());
+    }
+    browse_archive(&archive.path)
+}
+
+fn browse_archive(archive: &str) -> Result<(), Box<dyn Error>> {
+    // unsafe entries are reported before the screen is taken over, and can not be extracted
+    let entries = path_safety::safe_entries(archive, &archive_entry::read_entries(archive)?)?;
+    let mut browser = Browser {
+        archive: archive.to_owned(),
+        tree: ArchiveTree::build(&entries),
+        entries,
+        expanded: HashSet::new(),
+        selected: HashSet::new(),
+        cursor: 0,
+        scroll: 0,
+        message: String::new(),
+    };
+
+    let terminal = Terminal::enter()?;
+    loop {
+        let rows = browser.tree.visible_rows(&browser.expanded);
+        let (height, _) = terminal.size();
+        terminal.draw(&browser.render(&rows, height))?;
+
+        let current = rows.get(browser.cursor).map(|(_, node)| *node);
+        match (terminal.read_key()?, current) {
+            (Key::Char('q') | Key::Escape | Key::Eof, _) => break,
+            (Key::Up | Key::Char('k'), _) => browser.cursor = browser.cursor.saturating_sub(1),
+            (Key::Down | Key::Char('j'), _) => {
+                browser.cursor = (browser.cursor + 1).min(rows.len().saturating_sub(1))
+            }
+            (Key::Right | Key::Char('l') | Key::Enter, Some(node)) => {
+                match browser.tree.nodes[node].is_directory {
+                    true => _ = browser.expanded.insert(node),
+                    false => browser.preview(&terminal, node)?,
+                }
+            }
+            (Key::Left | Key::Char('h'), Some(node)) => browser.collapse(node, &rows),
+            (Key::Space, Some(node)) => browser.toggle_selection(node),
+            (Key::Char('p'), Some(node)) => browser.preview(&terminal, node)?,
+            (Key::Char('x'), Some(node)) => browser.extract(&terminal, node)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+impl Browser {
+    fn render(&mut self, rows: &[(usize, usize)], height: usize) -> Vec<String> {
+        let list_height = height.saturating_sub(HEADER_LINES + FOOTER_LINES).max(1);
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + list_height {
+            self.scroll = self.cursor + 1 - list_height;
+        }
+
+        let mut lines = vec![
+            format!(
+                "{} - {} entries, {} selected",
+                self.archive,
+                self.entries.len(),
+                self.selected.len()
+            ),
+            HELP.to_owned(),
+        ];
+        for (idx, (depth, node)) in rows.iter().enumerate().skip(self.scroll).take(list_height) {
+            let pointer = if idx == self.cursor { '>' } else { ' ' };
+            lines.push(format!(
+                "{pointer} {} {}{}",
+                self.selection_marker(*node),
+                "  ".repeat(*depth),
+                self.node_label(*node)
+            ));
+        }
+        while lines.len() < HEADER_LINES + list_height {
+            lines.push(String::new());
+        }
+
+        lines.push("-".repeat(40));
+        let current = rows.get(self.cursor).map(|(_, node)| *node);
+        lines.extend(self.metadata_lines(current));
+        lines.push(self.message.to_owned());
+        lines
+    }
+
+    fn selection_marker(&self, node: usize) -> &'static str {
+        let files = self.tree.files_under(node);
+        let selected = files
+            .iter()
+            .filter(|file| self.selected.contains(file))
+            .count();
+        match selected {
+            0 => "[ ]",
+            _ if selected == files.len() => "[x]",
+            _ => "[-]",
+        }
+    }
+
+    fn node_label(&self, node: usize) -> String {
+        let tree_node = &self.tree.nodes[node];
+        match tree_node.is_directory {
+            true if self.expanded.contains(&node) => format!("v {}/", tree_node.name),
+            true => format!("> {}/", tree_node.name),
+            false => format!("  {}", tree_node.name),
+        }
+    }
+
+    fn metadata_lines(&self, node: Option<usize>) -> Vec<String> {
+        let entry = node
+            .and_then(|node| self.tree.nodes[node].entry)
+            .map(|idx| &self.entries[idx]);
+        let Some(entry) = entry else {
+            return vec![
+                "directory (no entry in the archive)".to_owned(),
+                String::new(),
+            ];
+        };
+        let compressed = match entry.compressed_size {
+            Some(size) => format!(", {size} compressed"),
+            None => String::new(),
+        };
+        let link = match &entry.link_target {
+            Some(target) => format!("-> {target}"),
+            None => String::new(),
+        };
+        vec![
+            format!(
+                "{}  {}  {} bytes{compressed}  {}  {}{}",
+                entry.kind.name(),
+                entry.mode,
+                entry.size,
+                entry.mtime,
+                entry.owner.as_deref().unwrap_or("-"),
+                if entry.encrypted { "  encrypted" } else { "" }
+            ),
+            link,
+        ]
+    }
+
+    /// Closes the expanded directory or moves to the parent one.
+    fn collapse(&mut self, node: usize, rows: &[(usize, usize)]) {
+        if self.expanded.remove(&node) {
+            return;
+        }
+        if let Some(parent) = self.tree.parent_of(node).filter(|parent| *parent != ROOT) {
+            self.expanded.remove(&parent);
+            if let Some(idx) = rows.iter().position(|(_, row)| *row == parent) {
+                self.cursor = idx;
+            }
+        }
+    }
+
+    /// Selects every file below the node, or deselects them when all are already selected.
+    fn toggle_selection(&mut self, node: usize) {
+        let files = self.tree.files_under(node);
+        match files.iter().all(|file| self.selected.contains(file)) {
+            true => files.iter().for_each(|file| _ = self.selected.remove(file)),
+            false => self.selected.extend(files),
+        }
+    }
+
+    fn preview(&mut self, terminal: &Terminal, node: usize) -> Result<(), Box<dyn Error>> {
+        let tree_node = &self.tree.nodes[node];
+        let entry = match tree_node.entry.map(|idx| &self.entries[idx]) {
+            Some(entry) if entry.kind == ArchiveEntryKind::File => entry,
+            _ => {
+                self.message = format!("Can not preview '{}'.", tree_node.path);
+                return Ok(());
+            }
+        };
+        let cmd = format!(
+            "{} | head -c {PREVIEW_BYTES}",
+            cat::cat_cmd(&self.archive, &entry.path)
+        );
+        let content = execute_cmd_get_lines(&cmd);
+        if content.iter().any(|line| line.contains('\0')) {
+            self.message = format!("'{}' is a binary entry.", tree_node.path);
+            return Ok(());
+        }
+        let content = content
+            .iter()
+            .map(|line| printable(line))
+            .collect::<Vec<String>>();
+
+        let mut offset = 0;
+        loop {
+            let (height, _) = terminal.size();
+            let page = height.saturating_sub(HEADER_LINES).max(1);
+            let mut lines = vec![
+                format!("{} - {}", self.archive, tree_node.path),
+                "up/down scroll  space next page  q back".to_owned(),
+            ];
+            lines.extend(content.iter().skip(offset).take(page).cloned());
+            terminal.draw(&lines)?;
+
+            let last_page = content.len().saturating_sub(page);
+            match terminal.read_key()? {
+                Key::Up | Key::Char('k') => offset = offset.saturating_sub(1),
+                Key::Down | Key::Char('j') => offset = (offset + 1).min(last_page),
+                Key::Space => offset = (offset + page).min(last_page),
+                Key::Char('q') | Key::Escape | Key::Eof | Key::Left | Key::Char('h') => break,
+                _ => {}
+            }
+        }
+        self.message.clear();
+        Ok(())
+    }
+
+    /// Extracts the selected entries, or the one under the cursor when nothing is selected.
+    fn extract(&mut self, terminal: &Terminal, node: usize) -> Result<(), Box<dyn Error>> {
+        let nodes = match self.selected.is_empty() {
+            true => self.tree.files_under(node),
+            false => self.selected.iter().copied().collect(),
+        };
+        let selected_entries = nodes
+            .iter()
+            .filter_map(|node| self.tree.nodes[*node].entry)
+            .map(|idx| self.entries[idx].clone())
+            .collect::<Vec<ArchiveEntry>>();
+        if selected_entries.is_empty() {
+            self.message = "Nothing to extract.".to_owned();
+            return Ok(());
+        }
+        if let Err(error) = limits::check_entries(&self.archive, &selected_entries) {
+            self.message = error.to_string();
+            return Ok(());
+        }
+        let mut members = selected_entries;
+        members.sort_by(|a, b| a.path.cmp(&b.path));
+
+        terminal.suspend()?;
+        let default_dir = command_builder::resolve_output_dir(None)?;
+        let target_dir = input_handler::read_optional(&format!(
+            "Extract {} entries to directory (leave empty for {default_dir}):",
+            members.len()
+        ));
+        let target_dir = command_builder::resolve_output_dir(target_dir.as_deref())?;
+        terminal.resume()?;
+
+        let result = fs::create_dir_all(&target_dir)
+            .map_err(|error| error.into())
+            .and_then(|_| unpacker::extract_members(&self.archive, &target_dir, &members));
+        self.message = match result {
+            Ok(_) => {
+                self.selected.clear();
+                format!("Extracted {} entries to {target_dir}.", members.len())
+            }
+            Err(error) => format!("Extraction failed: {error}"),
+        };
+        Ok(())
+    }
+}
+
+/// Tabs expanded and other control characters replaced, so they can not move the cursor.
+fn printable(line: &str) -> String {
+    line.replace('\t', "    ")
+        .chars()
+        .map(|c| if c.is_control() { '?' } else { c })
+        .collect()
+}
@@ -0,0 +1,3 @@
+pub mod archive_tree;
+pub mod browser;
+pub mod terminal;
@@ -0,0 +1,133 @@
+use crate::cmd::command_runner::{execute_cmd, execute_cmd_get_lines};
+use std::cell::Cell;
+use std::error::Error;
+use std::io::{Read, Write, stdin, stdout};
+
+const ENTER_ALTERNATE_SCREEN: &str = "\x1b[?1049h\x1b[?25l";
+const LEAVE_ALTERNATE_SCREEN: &str = "\x1b[?25h\x1b[?1049l";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Space,
+    Escape,
+    Char(char),
+    /// End of input, e.g. the terminal went away.
+    Eof,
+    Other,
+}
+
+/// Full-screen raw mode terminal set up with `stty`, restored when dropped.
+pub struct Terminal {
+    saved_settings: String,
+    /// Read once per `resume`, not on every draw.
+    size: Cell<(usize, usize)>,
+}
+
+impl Terminal {
+    pub fn enter() -> Result<Terminal, Box<dyn Error>> {
+        let saved_settings = execute_cmd_get_lines("stty -g < /dev/tty")
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        let terminal = Terminal {
+            saved_settings,
+            size: Cell::new((24, 80)),
+        };
+        terminal.resume()?;
+        Ok(terminal)
+    }
+
+    pub fn resume(&self) -> Result<(), Box<dyn Error>> {
+        execute_cmd("stty raw -echo < /dev/tty")?;
+        self.size.set(read_size());
+        print!("{ENTER_ALTERNATE_SCREEN}");
+        stdout().flush()?;
+        Ok(())
+    }
+
+    /// Back to the regular screen, e.g. to read a line of input.
+    pub fn suspend(&self) -> Result<(), Box<dyn Error>> {
+        print!("{LEAVE_ALTERNATE_SCREEN}");
+        stdout().flush()?;
+        match self.saved_settings.is_empty() {
+            true => execute_cmd("stty sane < /dev/tty"),
+            false => execute_cmd(&format!("stty '{}' < /dev/tty", self.saved_settings)),
+        }
+    }
+
+    /// `(rows, columns)`, 24x80 when it can not be determined.
+    #[inline]
+    pub fn size(&self) -> (usize, usize) {
+        self.size.get()
+    }
+
+    pub fn read_key(&self) -> Result<Key, Box<dyn Error>> {
+        let mut buffer = [0u8; 8];
+        let read = stdin().read(&mut buffer)?;
+        Ok(parse_key(&buffer[..read]))
+    }
+
+    /// Replaces the whole screen with the lines, cut to the terminal width.
+    pub fn draw(&self, lines: &[String]) -> Result<(), Box<dyn Error>> {
+        let (rows, cols) = self.size();
+        let mut out = stdout().lock();
+        write!(out, "\x1b[H\x1b[2J")?;
+        for line in lines.iter().take(rows) {
+            let line = line.chars().take(cols).collect::<String>();
+            write!(out, "{line}\r\n")?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = self.suspend();
+    }
+}
+
+fn read_size() -> (usize, usize) {
+    let lines = execute_cmd_get_lines("stty size < /dev/tty");
+    let size = lines
+        .first()
+        .and_then(|line| line.split_once(' '))
+        .and_then(|(rows, cols)| Some((rows.parse().ok()?, cols.parse().ok()?)));
+    size.unwrap_or((24, 80))
+}
+
+fn parse_key(bytes: &[u8]) -> Key {
+    match bytes {
+        [] => Key::Eof,
+        [0x1b, b'[', b'A', ..] => Key::Up,
+        [0x1b, b'[', b'B', ..] => Key::Down,
+        [0x1b, b'[', b'C', ..] => Key::Right,
+        [0x1b, b'[', b'D', ..] => Key::Left,
+        [0x1b] => Key::Escape,
+        [b'\r', ..] | [b'\n', ..] => Key::Enter,
+        [b' ', ..] => Key::Space,
+        [byte, ..] if byte.is_ascii_graphic() => Key::Char(*byte as char),
+        _ => Key::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_keys() {
+        assert_eq!(parse_key(b"\x1b[A"), Key::Up);
+        assert_eq!(parse_key(b"\x1b[D"), Key::Left);
+        assert_eq!(parse_key(b"\x1b"), Key::Escape);
+        assert_eq!(parse_key(b"\r"), Key::Enter);
+        assert_eq!(parse_key(b"q"), Key::Char('q'));
+        assert_eq!(parse_key(b""), Key::Eof);
+        assert_eq!(parse_key(b"\x01"), Key::Other);
+    }
+}